@@ -1,6 +1,55 @@
-use clap::{App, Arg, ArgMatches};
+use crate::cfg::{parse_time, parse_week};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand, Values};
+use food::price::parse_money;
+use food::target::Target;
+use std::ffi::OsString;
+
+/// The options given before and after a subcommand, so that `food -l 2 shop` is the same as
+/// `food shop -l 2`. Options given after the subcommand take precedence, followed by options given
+/// before it, and then the default values of the subcommand.
+pub struct Matches<'a> {
+    pub top: &'a ArgMatches<'a>,
+    pub sub: &'a ArgMatches<'a>,
+}
+
+impl<'a> Matches<'a> {
+    fn pick(&self, name: &str) -> &'a ArgMatches<'a> {
+        if self.sub.occurrences_of(name) > 0 {
+            self.sub
+        } else if self.top.occurrences_of(name) > 0 || self.sub.value_of(name).is_none() {
+            self.top
+        } else {
+            self.sub
+        }
+    }
+
+    pub fn value_of(&self, name: &str) -> Option<&'a str> {
+        self.pick(name).value_of(name)
+    }
+
+    pub fn values_of(&self, name: &str) -> Option<Values<'a>> {
+        self.pick(name).values_of(name)
+    }
+
+    pub fn is_present(&self, name: &str) -> bool {
+        self.pick(name).is_present(name)
+    }
+
+    pub fn occurrences_of(&self, name: &str) -> u64 {
+        self.pick(name).occurrences_of(name)
+    }
+}
 
 pub fn args<'a>() -> ArgMatches<'a> {
+    parse_args(std::env::args_os())
+}
+
+/// Parse the command line arguments, where the first argument is the name of the application
+pub fn parse_args<'a, I, T>(args: I) -> ArgMatches<'a>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
     let path = Arg::with_name("path")
         .default_value(".")
         .takes_value(true)
@@ -27,21 +76,158 @@ pub fn args<'a>() -> ArgMatches<'a> {
         .help("Set seed value")
         .long_help("Set the seed value which will be used to seed the random generator. Setting a different seed value will change which recipes are selected. The seed value is automatically updated on weekly basis.");
 
+    let pin = Arg::with_name("pin")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .short("p")
+        .long("pin")
+        .help("Always include a recipe")
+        .long_help("Always include the recipe with the given path or title in the selection. The remaining recipes, up to the limit, will be selected randomly as usual. This option can be given several times to pin several recipes.");
+
+    let exclude = Arg::with_name("exclude")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .short("x")
+        .long("exclude")
+        .help("Exclude recipes with an ingredient")
+        .long_help("Exclude all recipes containing the given ingredient, or any ingredient in the given group. Available groups are 'nuts', 'gluten', 'dairy', 'eggs' and 'shellfish'. This option can be given several times. Ingredients or groups that should always be excluded can be listed in the file 'exclude.md' in the config directory (~/.config/food), one per line and preceded by a dash.");
+
     let simple = Arg::with_name("simple")
         .short("s")
         .long("simple")
+        .conflicts_with("prefer")
         .help("Prefer simple recipes")
-        .long_help("Only use simple recipes, with less ingredients, as far as possible");
+        .long_help("Only use simple recipes, with less ingredients and instructions, as far as possible. This is the same as '--prefer simple'.");
+
+    let prefer = Arg::with_name("prefer")
+        .takes_value(true)
+        .possible_values(&["simple", "varied"])
+        .long("prefer")
+        .help("Prefer simple or varied recipes")
+        .long_help("Prefer recipes of a certain complexity. With 'simple', recipes that are no more complex than the median recipe are selected as far as possible. With 'varied', a mix of simple, average and complex recipes are selected. The complexity of a recipe is based on its number of ingredients, number of instructions and total cooking time (given as for example 'time: 1 h 30 min' in the front matter of the recipe).");
+
+    let max_complexity = Arg::with_name("max_complexity")
+        .takes_value(true)
+        .validator(is_digit)
+        .long("max-complexity")
+        .help("Set max complexity of recipes")
+        .long_help("Never select recipes with a complexity higher than this, unless pinned. The complexity of a recipe is one point per ingredient, one point per instruction and one point per 15 minutes of total cooking time.");
+
+    let strategy = Arg::with_name("strategy")
+        .takes_value(true)
+        .default_value("random")
+        .possible_values(&["random", "overlap"])
+        .long("strategy")
+        .help("Set strategy for selecting recipes")
+        .long_help("Set the strategy for how recipes are selected. With 'random', recipes are selected at random. With 'overlap', recipes that share ingredients with each other are preferred, so that less of what is bought goes to waste. Ingredients listed in the file 'perishables.md' in the config directory (~/.config/food) are given extra weight when using 'overlap'.");
 
     let serving_size = Arg::with_name("serving_size")
+        .global(true)
         .short("z")
         .takes_value(true)
-        .validator(is_digit)
+        .validator(is_servings)
         .long("serving-size")
         .help("Set serving size")
         .long_help("Set a custom serving size for each recipe");
 
+    let pantry = Arg::with_name("pantry")
+        .takes_value(true)
+        .long("pantry")
+        .help("Set pantry file")
+        .long_help("Set the file with ingredients that are already at home, which will be subtracted from the shopping list. Each ingredient is listed on a line of its own, preceded by a dash, optionally with the amount on hand (such as '- milk, 1 l'). Ingredients without an amount are staples, which are always assumed to be in stock. Defaults to the file 'pantry.md' in the config directory (~/.config/food), if it exists.");
+
+    let group = Arg::with_name("group")
+        .short("g")
+        .long("group")
+        .help("Group shopping list by category")
+        .long_help("Group the ingredients on the shopping list by category, such as produce, dairy, meat, dry goods, spices and frozen, in the order they are found in the store. Categories can be added, reordered or have their ingredients changed in the file 'categories.md' in the config directory (~/.config/food), where each category is a heading followed by its ingredients, one per line and preceded by a dash. Ingredients that do not belong to any category are listed last, as uncategorized.");
+
+    let packages = Arg::with_name("packages")
+        .takes_value(true)
+        .default_value("cheapest")
+        .possible_values(&["cheapest", "fewest"])
+        .long("packages")
+        .help("Set how to round to package sizes")
+        .long_help("Set how ingredients on the shopping list are rounded up to the package sizes they can be bought in, which are listed in the file 'packages.md' in the config directory (~/.config/food). Each ingredient is listed on a line of its own, preceded by a dash and followed by its package sizes, such as '- cream, 25 cl carton 9.50, 5 dl carton 15.00', where the price is optional. With 'cheapest', the cheapest combination of packages is bought, if all package sizes of the ingredient have a price. With 'fewest', or when prices are missing, as few packages as possible are bought.");
+
+    let prices = Arg::with_name("prices")
+        .takes_value(true)
+        .long("prices")
+        .help("Set price list file")
+        .long_help("Set the price list, in CSV format, which is used to estimate the cost of each selected recipe and of the shopping list. Each line has the columns ingredient, package size, price and currency, such as 'canned tomatoes,400 g,12.90,SEK'. Columns may also be separated by semicolons, with decimal commas. Defaults to the file 'prices.csv' in the config directory (~/.config/food), if it exists.");
+
+    let budget = Arg::with_name("budget")
+        .takes_value(true)
+        .validator(is_money)
+        .long("budget")
+        .help("Set max cost of selected recipes")
+        .long_help("Set the maximum estimated cost of all ingredients of the selected recipes, in the currency of the price list (see --prices). The most expensive recipes are swapped for cheaper ones until the cost is within the budget, as far as possible. Pinned recipes are never swapped.");
+
+    let nutrition = Arg::with_name("nutrition")
+        .takes_value(true)
+        .long("nutrition")
+        .help("Set nutrition table file")
+        .long_help("Set the nutrition table, in CSV format, which is used to print the nutrition per serving of each selected recipe, and in total for all selected recipes. Each line has the columns name, kcal, protein, fat and carbs per 100 g, optionally followed by density (g/ml) for ingredients measured by volume and weight of one piece (g) for ingredients counted in pieces, such as 'milk,60,3.5,3,5,1.03'. Columns may also be separated by semicolons, with decimal commas. Defaults to the file 'nutrition.csv' in the config directory (~/.config/food), if it exists.");
+
+    let target = Arg::with_name("target")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .validator(|t: String| t.parse::<Target>().map(|_| ()))
+        .long("target")
+        .help("Set a nutrition target")
+        .long_help("Set a target for the nutrition per serving of the selected recipes, which requires a nutrition table (see --nutrition). A target is a nutrient (kcal, protein, fat or carbs), followed by '>=' or '<=' and a value, such as 'protein >= 25', which applies to every recipe. Start with 'avg' to apply it to the average of all recipes instead, such as 'avg kcal <= 700'. Recipes are swapped for other candidates to meet the targets as far as possible, and targets that are not met are reported. This option can be given several times.");
+
+    let sources = Arg::with_name("sources")
+        .long("sources")
+        .help("Show which recipes ingredients are for")
+        .long_help("Show which recipes, and which part of each recipe (such as a sauce), every ingredient on the shopping list is needed for, and how much of it each recipe needs. This makes it easy to remove a recipe from the shopping list afterwards.");
+
+    let format = Arg::with_name("format")
+        .takes_value(true)
+        .default_value("text")
+        .possible_values(&["text", "json", "markdown", "csv", "html", "ics"])
+        .long("format")
+        .help("Set output format of shopping list")
+        .long_help("Set the output format of the shopping list. With 'text', the selected recipes, the shopping list, and the cost and nutrition of the recipes when known, are printed to be read by humans. With 'json', a single JSON document with the selected recipes and the shopping list is printed, to be read by other programs. The schema of the JSON document is described in the README. With 'markdown', the recipes and a checklist of the ingredients to buy are printed, grouped by category with --group. With 'csv', the ingredients to buy are printed with the columns item, amount, unit, category and recipes, for spreadsheets. With 'html', a self-contained page meant to be printed is generated, with the recipes linked to their files and a checklist of the ingredients to buy, grouped by category. With 'ics', an iCalendar file is generated with one event per selected recipe, on each day of the current week (or the week given with --week), starting with the first recipe on Sunday, to be imported into a calendar.");
+
+    let units = Arg::with_name("units")
+        .takes_value(true)
+        .default_value("metric")
+        .possible_values(&["metric", "imperial"])
+        .long("units")
+        .help("Set system of units that amounts are shown in")
+        .long_help("Set the system of units that amounts are shown in. With 'metric', amounts are shown in grams, kilograms, milliliters, deciliters and liters. With 'imperial', amounts are shown in ounces, pounds, teaspoons, tablespoons, fluid ounces, cups and pints, rounded to whole units. Amounts in the JSON output are always in grams and milliliters.");
+
+    let dinner = Arg::with_name("dinner")
+        .takes_value(true)
+        .validator(is_time)
+        .long("dinner")
+        .help("Set time of dinner in calendar")
+        .long_help("Set the time of dinner, such as '18:30', for the events of the iCalendar output (see --format). Each event then starts at that time and lasts for an hour. Without a time, the events last all day.");
+
+    let week = Arg::with_name("week")
+        .takes_value(true)
+        .validator(is_date)
+        .long("week")
+        .help("Set week of calendar")
+        .long_help("Set the week of the events of the iCalendar output (see --format) by any date in it, such as '2026-10-19'. The events start on the Sunday of that week. Without a date, the events are in the current week.");
+
+    let template = Arg::with_name("template")
+        .takes_value(true)
+        .long("template")
+        .help("Set template for shopping list")
+        .long_help("Set a template file which the selected recipes and the shopping list are printed with, instead of any of the formats of --format. Templates use a subset of the Handlebars syntax, such as '{{#each shopping_list}}- {{item}}, {{quantity}}{{/each}}', and have access to the same fields as the JSON output, see the README for details.");
+
+    let recipe_text = Arg::with_name("recipe_text")
+        .long("recipe-text")
+        .help("Include recipes in HTML output")
+        .long_help("Include the full text of each selected recipe in the HTML output (see --format), with each recipe on a page of its own when printed, so that the recipes are available offline.");
+
     let verbosity = Arg::with_name("verbosity")
+        .global(true)
         .takes_value(true)
         .default_value("1")
         .validator(|n: String| {
@@ -65,25 +251,249 @@ pub fn args<'a>() -> ArgMatches<'a> {
         .help("Print debug information")
         .long_help("Print debug information about current build for binary, useful for when an issue is encountered and reported");
 
+    let recipe = Arg::with_name("recipe")
+        .takes_value(true)
+        .required(true)
+        .help("Path to, or title of, recipe to cook");
+
+    let servings = Arg::with_name("servings")
+        .takes_value(true)
+        .required(true)
+        .validator(is_servings)
+        .help("Number of servings to scale to");
+
+    let cook = SubCommand::with_name("cook")
+        .about("Remove the ingredients of a cooked recipe from the pantry")
+        .long_about("Remove the ingredients of a cooked recipe, scaled to the serving size if one is given, from the pantry. Ingredients in the pantry that drop below their minimum amount will be reported, and added to the next shopping list.")
+        .arg(recipe.clone())
+        .arg(pantry.clone())
+        .arg(path.clone());
+
+    // Options for selecting recipes and printing the shopping list, which are used by the
+    // default command as well as by the commands that select recipes the same way
+    let shop_args: Vec<Arg> = vec![
+        limit,
+        seed,
+        pin,
+        exclude,
+        simple,
+        prefer,
+        max_complexity,
+        strategy,
+        pantry,
+        group,
+        sources,
+        packages,
+        prices,
+        budget,
+        nutrition,
+        target,
+        format,
+        units.clone(),
+        recipe_text,
+        template,
+        dinner,
+        week,
+    ];
+
+    let shop = SubCommand::with_name("shop")
+        .about("Generate a shopping list, which is the default command")
+        .long_about("Select recipes and generate a shopping list for them. This is the default command, which is run when no other command is given.")
+        .args(&shop_args)
+        .arg(path.clone());
+
+    let confirm_plan = SubCommand::with_name("confirm-plan")
+        .about("Remove the ingredients of all selected recipes from the pantry")
+        .long_about("Remove the ingredients of all recipes that are selected with the given options, which are the same recipes as on the shopping list generated with the same options, from the pantry. Ingredients in the pantry that drop below their minimum amount will be reported, and added to the next shopping list.")
+        .args(&shop_args)
+        .arg(path.clone());
+
+    let show = SubCommand::with_name("show")
+        .about("Print a recipe")
+        .long_about("Print the title, servings, cooking time, rating and ingredients of a recipe, scaled to the serving size if one is given, followed by the path to the recipe file.")
+        .arg(recipe.clone())
+        .arg(units)
+        .arg(path.clone());
+
+    let scale = SubCommand::with_name("scale")
+        .about("Print a recipe scaled to a number of servings")
+        .long_about("Print the full text of a recipe with the amount of every ingredient scaled to the given number of servings, and the servings changed accordingly, so that it can be saved as a recipe of its own. The recipe must state its number of servings.")
+        .arg(recipe)
+        .arg(servings)
+        .arg(path.clone());
+
+    let sort = Arg::with_name("sort")
+        .takes_value(true)
+        .default_value("title")
+        .possible_values(&["title", "path", "servings", "ingredients"])
+        .long("sort")
+        .help("Set what to sort recipes by")
+        .long_help("Set what the recipes are sorted by, which is their title, the path to their file, their number of servings or their number of ingredients. Recipes with the same value are sorted by title.");
+
+    let reverse = Arg::with_name("reverse")
+        .short("r")
+        .long("reverse")
+        .help("Sort recipes in reverse order");
+
+    let list_format = Arg::with_name("format")
+        .takes_value(true)
+        .default_value("text")
+        .possible_values(&["text", "json"])
+        .long("format")
+        .help("Set output format of list")
+        .long_help("Set the output format of the list. With 'text', the recipes are printed as a table, and with 'json', as a JSON document with the same fields as the table, to be read by other programs.");
+
+    let list = SubCommand::with_name("list")
+        .about("List all recipes")
+        .long_about("List all recipes that are found in the given paths, which are the same recipes as the shopping list is generated from, with the title, number of servings, number of ingredients, tags and path of each recipe.")
+        .arg(sort)
+        .arg(reverse)
+        .arg(list_format)
+        .arg(path.clone());
+
+    let with = Arg::with_name("with")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .required_unless_one(&["without", "query"])
+        .short("w")
+        .long("with")
+        .help("Find recipes with an ingredient")
+        .long_help("Find recipes that use the given ingredient, such as something left in the fridge. This option can be given several times, and the recipes that use the most of the ingredients are listed first, followed by the recipes that need the fewest other ingredients.");
+
+    let without = Arg::with_name("without")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .long("without")
+        .help("Find recipes without an ingredient")
+        .long_help("Only find recipes that do not contain the given ingredient, or any ingredient in the given group, the same way as --exclude. This option can be given several times.");
+
+    let query = Arg::with_name("query")
+        .takes_value(true)
+        .help("Words to search recipes for")
+        .long_help("Words to search the titles, tags, ingredients and instructions of recipes for, such as 'lemon pasta'. Recipes that contain the most of the words come first. Several words must be quoted, since the following values are paths to look for recipes in. A query can not be combined with --with, in which case all values are paths.");
+
+    let max_results = Arg::with_name("max_results")
+        .takes_value(true)
+        .default_value("10")
+        .validator(is_digit)
+        .short("n")
+        .long("max-results")
+        .help("Set max number of recipes found")
+        .long_help("Set the maximum number of recipes that are printed when searching for a query");
+
+    let search = SubCommand::with_name("search")
+        .about("Find recipes by their text or ingredients")
+        .long_about("Find recipes that contain the words of the query, using an index of all recipes which is kept in the cache directory (~/.cache/food) and updated with the recipes that have changed since the last search. Each recipe is printed with its path and the part of the recipe where the words are found, with the words highlighted. Alternatively, find recipes that use the ingredients given with --with, ranked by how many of the ingredients they use, with the other ingredients each recipe needs. Recipes with any of the ingredients given with --without are left out. Ingredients are matched by name, ignoring plural forms, so that 'mushroom' matches 'button mushrooms'.")
+        .arg(query)
+        .arg(with)
+        .arg(without)
+        .arg(max_results)
+        .arg(path.clone());
+
+    let lint = SubCommand::with_name("lint")
+        .about("Check recipes for problems")
+        .long_about("Check all recipes for problems, such as a missing title or servings, ingredients that cannot be parsed, and titles used by more than one recipe. Each problem is printed with the path to the recipe, and the exit code is 6 if any problem is found.")
+        .arg(path.clone());
+
+    let stats = SubCommand::with_name("stats")
+        .about("Print statistics about the recipes")
+        .long_about("Print statistics about all recipes, such as the number of recipes and ingredients, the average cooking time and the most common ingredients.")
+        .arg(path.clone());
+
+    let config_show = SubCommand::with_name("show")
+        .about("Print the effective config")
+        .long_about("Print the effective config, which is the options given on the command line, in environment variables, in the config file of the current directory (.food.toml) and in the config file of the user (config.toml in the config directory), in that order of precedence, and otherwise the default values. Each option is followed by a comment telling where its value comes from.")
+        .args(&shop_args)
+        .arg(path.clone());
+
+    let config = SubCommand::with_name("config")
+        .about("Inspect the config")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(config_show);
+
     let args: ArgMatches = App::new(crate_name!())
         .about("Application for generating shopping lists from recipes")
         .version(crate_version!())
         .author(crate_authors!())
         .arg(path)
-        .arg(limit)
-        .arg(seed)
-        .arg(simple)
+        .args(&shop_args)
         .arg(serving_size)
         .arg(verbosity)
         .arg(debug)
-        .get_matches();
+        .subcommand(shop)
+        .subcommand(list)
+        .subcommand(search)
+        .subcommand(show)
+        .subcommand(scale)
+        .subcommand(lint)
+        .subcommand(stats)
+        .subcommand(cook)
+        .subcommand(confirm_plan)
+        .subcommand(config)
+        .get_matches_from(args);
 
     args
 }
 
+fn is_money(input: String) -> Result<(), String> {
+    match parse_money(&input) {
+        Some(_) => Ok(()),
+        None => Err(format!("Invalid amount: {}", input)),
+    }
+}
+
+fn is_time(input: String) -> Result<(), String> {
+    match parse_time(&input) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "Invalid time, expected hours and minutes such as 18:30: {}",
+            input
+        )),
+    }
+}
+
+fn is_date(input: String) -> Result<(), String> {
+    match parse_week(&input) {
+        Some(_) => Ok(()),
+        None => Err(format!(
+            "Invalid date, expected year, month and day such as 2026-10-19: {}",
+            input
+        )),
+    }
+}
+
+fn is_servings(input: String) -> Result<(), String> {
+    match input.parse::<u8>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(format!(
+            "Invalid number of servings, expected 1 to {}: {}",
+            u8::MAX,
+            input
+        )),
+    }
+}
+
 fn is_digit(input: String) -> Result<(), String> {
     match input.parse::<u32>() {
         Ok(_) => Ok(()),
         Err(e) => Err(e.to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::args::is_servings;
+
+    #[test]
+    fn test_is_servings() {
+        assert!(is_servings("4".to_string()).is_ok());
+        assert!(is_servings("255".to_string()).is_ok());
+        assert_eq!(
+            Err("Invalid number of servings, expected 1 to 255: 300".to_string()),
+            is_servings("300".to_string())
+        );
+        assert!(is_servings("0".to_string()).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+use crate::exclude::contains_words;
+use crate::recipe::{list_items, Ingredient};
+use std::path::Path;
+
+/// Category for ingredients which does not belong to any other category, which is always last
+pub const UNCATEGORIZED: &str = "uncategorized";
+
+/// The default categories, in the order they are visited in a typical store, and the ingredients
+/// belonging to each category. When an ingredient matches several categories, the category with
+/// the longest (most specific) matching name is used, so "coconut milk" is dry goods rather than
+/// dairy.
+const DEFAULT_CATEGORIES: [(&str, &[&str]); 6] = [
+    (
+        "produce",
+        &[
+            "apple",
+            "avocado",
+            "banana",
+            "basil",
+            "bean sprout",
+            "bell pepper",
+            "broccoli",
+            "cabbage",
+            "carrot",
+            "cauliflower",
+            "celery",
+            "chili pepper",
+            "coriander",
+            "cucumber",
+            "dill",
+            "eggplant",
+            "garlic",
+            "garlic clove",
+            "ginger",
+            "herb",
+            "kale",
+            "leek",
+            "lemon",
+            "lettuce",
+            "lime",
+            "mint",
+            "mushroom",
+            "onion",
+            "orange",
+            "parsley",
+            "pear",
+            "potato",
+            "rosemary",
+            "salad",
+            "scallion",
+            "spinach",
+            "squash",
+            "thyme",
+            "tomato",
+            "zucchini",
+        ],
+    ),
+    (
+        "dairy",
+        &[
+            "butter",
+            "cheese",
+            "cream",
+            "creme fraiche",
+            "crème fraîche",
+            "egg",
+            "feta",
+            "fresh yeast",
+            "halloumi",
+            "mascarpone",
+            "milk",
+            "mozzarella",
+            "parmesan",
+            "quark",
+            "ricotta",
+            "sour cream",
+            "yoghurt",
+            "yogurt",
+        ],
+    ),
+    (
+        "meat",
+        &[
+            "bacon",
+            "beef",
+            "chicken",
+            "chorizo",
+            "cod",
+            "fish",
+            "ground beef",
+            "ham",
+            "lamb",
+            "minced meat",
+            "pork",
+            "prawn",
+            "salame",
+            "salami",
+            "salmon",
+            "sausage",
+            "shrimp",
+            "tuna",
+            "turkey",
+        ],
+    ),
+    (
+        "dry goods",
+        &[
+            "bread",
+            "breadcrumb",
+            "bulgur",
+            "canned tomato",
+            "chickpea",
+            "coconut milk",
+            "couscous",
+            "crushed tomato",
+            "flour",
+            "honey",
+            "lentil",
+            "noodle",
+            "nut",
+            "oat",
+            "oil",
+            "olive oil",
+            "pasta",
+            "rice",
+            "soy sauce",
+            "spaghetti",
+            "stock",
+            "sugar",
+            "tomato paste",
+            "vinegar",
+            "walnut",
+            "wheat flour",
+        ],
+    ),
+    (
+        "spices",
+        &[
+            "bay leaf",
+            "black pepper",
+            "cardamom",
+            "chili flake",
+            "cinnamon",
+            "cumin",
+            "curry",
+            "nutmeg",
+            "oregano",
+            "paprika",
+            "pepper",
+            "salt",
+            "saffron",
+            "turmeric",
+            "vanilla",
+        ],
+    ),
+    ("frozen", &["frozen", "ice cream", "peas"]),
+];
+
+/// Categories of ingredients, such as the aisles of a store, and the order in which the
+/// categories should be listed
+#[derive(Debug, Clone, Default)]
+pub struct Categories {
+    /// Categories given by the user, in the order they are listed, with their ingredients
+    custom: Vec<(String, Vec<String>)>,
+}
+
+impl Categories {
+    /// Parse categories where each category is a markdown heading, followed by the ingredients in
+    /// that category listed on a line of their own, preceded by a dash. The order of the headings
+    /// is the order in which categories are listed. Ingredients given here take precedence over the
+    /// default categories, and categories that are not listed here keep their default order, after
+    /// the listed categories.
+    pub fn parse(content: &str) -> Categories {
+        let mut custom: Vec<(String, Vec<String>)> = Vec::new();
+        for line in content.lines() {
+            let line: &str = line.trim();
+            if line.starts_with('#') {
+                let name: String = line.trim_start_matches('#').trim().to_lowercase();
+                custom.push((name, Vec::new()));
+            } else if let Some(item) = list_items(line).first() {
+                match custom.last_mut() {
+                    Some((_, items)) => items.push(item.to_lowercase()),
+                    None => log::warn!("Ingredient '{}' is not listed under any category", item),
+                }
+            }
+        }
+        Categories { custom }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Categories, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Categories::parse(&content)),
+            Err(e) => Err(format!("Unable to read categories from {:?}: {}", path, e)),
+        }
+    }
+
+    /// The category of the ingredient, or [UNCATEGORIZED] if it does not belong to any category
+    pub fn category(&self, item: &str) -> &str {
+        let custom = self
+            .custom
+            .iter()
+            .flat_map(|(name, items)| items.iter().map(move |i| (name.as_str(), i.as_str())));
+
+        if let Some(category) = most_specific(custom, item) {
+            return category;
+        }
+
+        let default = DEFAULT_CATEGORIES
+            .iter()
+            .flat_map(|(name, items)| items.iter().map(move |i| (*name, *i)));
+
+        most_specific(default, item).unwrap_or(UNCATEGORIZED)
+    }
+
+    /// All categories in the order they should be listed
+    pub fn layout(&self) -> Vec<&str> {
+        let mut layout: Vec<&str> = self.custom.iter().map(|(name, _)| name.as_str()).collect();
+        for (name, _) in DEFAULT_CATEGORIES.iter() {
+            if !layout.contains(name) {
+                layout.push(name);
+            }
+        }
+        layout.retain(|name| *name != UNCATEGORIZED);
+        layout.push(UNCATEGORIZED);
+        layout
+    }
+
+    /// Group the ingredients by category, with the categories in the order of the layout. Empty
+    /// categories are left out, and the order of the ingredients within each category is kept.
+    pub fn group(&self, ingredients: Vec<Ingredient>) -> Vec<(String, Vec<Ingredient>)> {
+        let mut groups: Vec<(String, Vec<Ingredient>)> = self
+            .layout()
+            .iter()
+            .map(|name| (name.to_string(), Vec::new()))
+            .collect();
+
+        for ingredient in ingredients {
+            let category: &str = self.category(&ingredient.item);
+            let index: usize = groups
+                .iter()
+                .position(|(name, _)| name == category)
+                .unwrap();
+            groups[index].1.push(ingredient);
+        }
+
+        groups.retain(|(_, items)| !items.is_empty());
+        groups
+    }
+}
+
+/// Find the category for the item among pairs of category and ingredient, where the longest
+/// matching ingredient wins
+fn most_specific<'a>(
+    categories: impl Iterator<Item = (&'a str, &'a str)>,
+    item: &str,
+) -> Option<&'a str> {
+    categories
+        .filter(|(_, ingredient)| contains_words(item, ingredient))
+        .max_by_key(|(_, ingredient)| ingredient.len())
+        .map(|(category, _)| category)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::category::{Categories, UNCATEGORIZED};
+    use crate::recipe::Ingredient;
+
+    #[test]
+    fn test_default_categories() {
+        let categories = Categories::default();
+        assert_eq!("produce", categories.category("red onions"));
+        assert_eq!("dairy", categories.category("milk"));
+        assert_eq!("dry goods", categories.category("coconut milk"));
+        assert_eq!("spices", categories.category("salt"));
+        assert_eq!(UNCATEGORIZED, categories.category("dish soap"));
+    }
+
+    #[test]
+    fn test_custom_categories_and_layout() {
+        let content = "## Bakery\n- sourdough bread\n## Dairy\n- oat milk\n## Produce\n";
+        let categories = Categories::parse(content);
+        assert_eq!("bakery", categories.category("sourdough bread"));
+        assert_eq!("dairy", categories.category("oat milk"));
+        assert_eq!(
+            vec![
+                "bakery",
+                "dairy",
+                "produce",
+                "meat",
+                "dry goods",
+                "spices",
+                "frozen"
+            ],
+            categories.layout()[..7].to_vec()
+        );
+        assert_eq!(Some(&UNCATEGORIZED), categories.layout().last());
+    }
+
+    #[test]
+    fn test_group_ingredients() {
+        let ingredients: Vec<Ingredient> = vec![
+            Ingredient::parse("- dish soap").unwrap(),
+            Ingredient::parse("- milk, 1 l").unwrap(),
+            Ingredient::parse("- onion, 2").unwrap(),
+            Ingredient::parse("- carrots, 3").unwrap(),
+        ];
+        let groups = Categories::default().group(ingredients);
+        let names: Vec<&str> = groups.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(vec!["produce", "dairy", UNCATEGORIZED], names);
+        assert_eq!("onion", groups[0].1[0].item);
+        assert_eq!("carrots", groups[0].1[1].item);
+    }
+}
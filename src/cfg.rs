@@ -1,29 +1,154 @@
-use chrono::Datelike;
+use crate::args::Matches;
+use crate::defaults::{effective, layers, Defaults, Layer, USER_FILE};
+use chrono::{Datelike, NaiveDate, NaiveTime};
 use clap::ArgMatches;
+use food::complexity::Preference;
+use food::config::config_file;
+use food::list::Sort;
+use food::package::Choice;
+use food::price::parse_money;
+use food::qty::Units;
+use food::select::Strategy;
+use food::shop::Format;
+use food::target::Target;
+
+pub enum Command {
+    /// Generate a shopping list
+    Shop,
+    /// List all recipes, sorted in the given order, or reversed if true
+    List(Sort, bool),
+    /// Find recipes by their text or their ingredients
+    Search(Query),
+    /// Print the recipe with the given path or title
+    Show(String),
+    /// Print the text of the recipe with the given path or title, scaled to a number of servings
+    Scale(String, u8),
+    /// Check all recipes for problems
+    Lint,
+    /// Print statistics about all recipes
+    Stats,
+    /// Remove the ingredients of the recipe with the given path or title from the pantry
+    Cook(String),
+    /// Remove the ingredients of all selected recipes from the pantry
+    ConfirmPlan,
+    /// Print the effective config, and where each value comes from
+    ShowConfig,
+}
+
+/// What to search recipes for
+pub struct Query {
+    /// Words to search the text of the recipes for, with the index
+    pub text: Option<String>,
+    /// Ingredients to find recipes with, when there is no text
+    pub with: Vec<String>,
+    pub without: Vec<String>,
+    /// Maximum number of recipes to print when searching for text
+    pub max_results: usize,
+}
 
 pub struct Config {
+    pub command: Command,
     pub paths: Vec<String>,
     pub limit: usize,
     pub seed: u64,
-    pub simple: bool,
+    pub pins: Vec<String>,
+    pub exclusions: Vec<String>,
+    pub preference: Option<Preference>,
+    pub max_complexity: Option<u32>,
+    pub strategy: Strategy,
     pub serving_size: Option<u8>,
+    pub pantry: Option<String>,
+    pub group: bool,
+    pub show_sources: bool,
+    pub package_choice: Choice,
+    pub prices: Option<String>,
+    /// Maximum estimated cost of the selected recipes, in cents
+    pub budget: Option<u32>,
+    pub nutrition: Option<String>,
+    pub targets: Vec<Target>,
+    pub format: Format,
+    /// The system of units that amounts are shown in
+    pub units: Units,
+    /// If the full text of each recipe is included in the HTML output
+    pub recipe_text: bool,
+    /// Template file to print the shopping list with, instead of [Config::format]
+    pub template: Option<String>,
+    /// Time of dinner, for the events of the iCalendar output
+    pub dinner: Option<NaiveTime>,
+    /// First day of the week of the iCalendar output, if another week than the current is given
+    pub week: Option<NaiveDate>,
     pub verbosity_level: u8,
     pub print_dbg: bool,
+    /// The sources of the options, most significant first
+    pub layers: Vec<Layer>,
+    /// Problems with the config files or environment variables, to be logged once logging is set up
+    pub warnings: Vec<String>,
 }
 
 impl Config {
     pub fn from_args(args: ArgMatches) -> Config {
-        let limit: usize = args
-            .value_of("limit")
-            .unwrap_or(&std::u64::MAX.to_string())
-            .parse()
-            .unwrap();
+        let (mut command, sub): (Command, &ArgMatches) = match args.subcommand() {
+            ("shop", Some(sub)) => (Command::Shop, sub),
+            ("list", Some(sub)) => {
+                let sort: Sort = sub.value_of("sort").unwrap().parse().unwrap();
+                (Command::List(sort, sub.is_present("reverse")), sub)
+            }
+            ("search", Some(sub)) => {
+                let values = |name: &str| -> Vec<String> {
+                    match sub.values_of(name) {
+                        Some(values) => values.map(|v| v.to_string()).collect(),
+                        None => Vec::new(),
+                    }
+                };
+                let query = Query {
+                    text: sub.value_of("query").map(|q| q.to_string()),
+                    with: values("with"),
+                    without: values("without"),
+                    max_results: sub.value_of("max_results").unwrap().parse().unwrap(),
+                };
+                (Command::Search(query), sub)
+            }
+            ("show", Some(sub)) => {
+                let recipe: String = sub.value_of("recipe").unwrap().to_string();
+                (Command::Show(recipe), sub)
+            }
+            ("scale", Some(sub)) => {
+                let recipe: String = sub.value_of("recipe").unwrap().to_string();
+                let servings: u8 = sub.value_of("servings").unwrap().parse().unwrap();
+                (Command::Scale(recipe, servings), sub)
+            }
+            ("lint", Some(sub)) => (Command::Lint, sub),
+            ("stats", Some(sub)) => (Command::Stats, sub),
+            ("cook", Some(sub)) => {
+                let recipe: String = sub.value_of("recipe").unwrap().to_string();
+                (Command::Cook(recipe), sub)
+            }
+            ("confirm-plan", Some(sub)) => (Command::ConfirmPlan, sub),
+            ("config", Some(sub)) => match sub.subcommand() {
+                ("show", Some(show)) => (Command::ShowConfig, show),
+                _ => (Command::ShowConfig, sub),
+            },
+            _ => (Command::Shop, &args),
+        };
+
+        let args = Matches { top: &args, sub };
+        let (layers, warnings): (Vec<Layer>, Vec<String>) = layers(&args, config_file(USER_FILE));
+        let defaults: Defaults = effective(&layers);
+
+        let limit: usize = defaults.limit.unwrap_or(usize::MAX);
+        let mut paths: Vec<String> = defaults.paths.unwrap_or_default();
 
-        let paths: Vec<String> = args
-            .values_of("path")
-            .unwrap()
-            .map(|v| v.to_string())
-            .collect();
+        // A query can not be combined with --with, so the first value is a path in that case
+        if let Command::Search(query) = &mut command {
+            if !query.with.is_empty() {
+                if let Some(path) = query.text.take() {
+                    if args.occurrences_of("path") == 0 {
+                        paths.clear();
+                    }
+                    paths.insert(0, path);
+                }
+            }
+        }
 
         let default_seed: String = gen_seed().to_string();
 
@@ -33,31 +158,156 @@ impl Config {
             .parse()
             .unwrap();
 
-        let simple: bool = args.is_present("simple");
+        let pins: Vec<String> = match args.values_of("pin") {
+            Some(pins) => pins.map(|v| v.to_string()).collect(),
+            None => Vec::new(),
+        };
+
+        let exclusions: Vec<String> = defaults.exclude.unwrap_or_default();
 
-        let serving_size: Option<u8> = match args.value_of("serving_size") {
+        let preference: Option<Preference> = match args.value_of("prefer") {
+            Some(prefer) => Some(prefer.parse().unwrap()),
+            None if defaults.simple == Some(true) => Some(Preference::Simple),
             None => None,
-            Some(n) => n.parse().ok(),
         };
 
+        let max_complexity: Option<u32> =
+            args.value_of("max_complexity").map(|n| n.parse().unwrap());
+
+        let strategy: Strategy = args
+            .value_of("strategy")
+            .map(|s| s.parse().unwrap())
+            .unwrap_or_default();
+
+        let serving_size: Option<u8> = defaults.serving_size;
+
+        let pantry: Option<String> = args.value_of("pantry").map(|p| p.to_string());
+
+        let group: bool = args.is_present("group");
+        let show_sources: bool = args.is_present("sources");
+        let package_choice: Choice = args
+            .value_of("packages")
+            .map(|p| p.parse().unwrap())
+            .unwrap_or_default();
+        let prices: Option<String> = args.value_of("prices").map(|p| p.to_string());
+        let budget: Option<u32> = args.value_of("budget").and_then(parse_money);
+        let nutrition: Option<String> = args.value_of("nutrition").map(|n| n.to_string());
+        let targets: Vec<Target> = match args.values_of("target") {
+            Some(targets) => targets.map(|t| t.parse().unwrap()).collect(),
+            None => Vec::new(),
+        };
+        let format: Format = defaults.format.unwrap_or_default();
+        let units: Units = defaults.units.unwrap_or_default();
+        let recipe_text: bool = args.is_present("recipe_text");
+        let template: Option<String> = args.value_of("template").map(|t| t.to_string());
+        let dinner: Option<NaiveTime> = args.value_of("dinner").and_then(parse_time);
+        let week: Option<NaiveDate> = args.value_of("week").and_then(parse_week);
+
         let verbosity_level: u8 = args.value_of("verbosity").unwrap().parse::<u8>().unwrap();
         let print_dbg: bool = args.is_present("debug");
 
         Config {
+            command,
             paths,
             limit,
             seed,
-            simple,
+            pins,
+            exclusions,
+            preference,
+            max_complexity,
+            strategy,
             serving_size,
+            pantry,
+            group,
+            show_sources,
+            package_choice,
+            prices,
+            budget,
+            nutrition,
+            targets,
+            format,
+            units,
+            recipe_text,
+            template,
+            dinner,
+            week,
             verbosity_level,
             print_dbg,
+            layers,
+            warnings,
         }
     }
 }
 
 /// The generated seed will be the number of whole weeks since UNIX epoch (January 1st 1970)
-fn gen_seed() -> u64 {
+pub fn gen_seed() -> u64 {
     let days: u64 = chrono::Utc::now().num_days_from_ce() as u64;
     let weeks: u64 = days / 7;
     weeks
 }
+
+/// Parse a time of day, such as `18:30`
+pub fn parse_time(input: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(input.trim(), "%H:%M").ok()
+}
+
+/// Parse a date, such as `2026-10-19`, into the first day of its week, which is a Sunday
+pub fn parse_week(input: &str) -> Option<NaiveDate> {
+    let date: NaiveDate = NaiveDate::parse_from_str(input.trim(), "%Y-%m-%d").ok()?;
+    let days: i32 = date.num_days_from_ce();
+    NaiveDate::from_num_days_from_ce_opt(days - days.rem_euclid(7))
+}
+
+/// The first day of the current week, which is the week that the generated seed is the number
+/// of, see [gen_seed]
+pub fn current_week() -> NaiveDate {
+    let days: u64 = gen_seed() * 7;
+    NaiveDate::from_num_days_from_ce_opt(days as i32).expect("The current week is a valid date")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::args::parse_args;
+    use crate::cfg::{current_week, parse_week, Command, Config};
+    use crate::defaults::show;
+    use chrono::{Datelike, NaiveDate, Weekday};
+    use food::shop::Format;
+
+    #[test]
+    fn test_options_before_subcommand() {
+        let limit = |args: &[&str]| Config::from_args(parse_args(args)).limit;
+        assert_eq!(2, limit(&["food", "-l", "2", "shop"]));
+        assert_eq!(2, limit(&["food", "shop", "-l", "2"]));
+        assert_eq!(3, limit(&["food", "-l", "2", "shop", "-l", "3"]));
+
+        let cfg = Config::from_args(parse_args([
+            "food", "-p", "Pizza", "--format", "json", "shop",
+        ]));
+        assert_eq!(vec!["Pizza".to_string()], cfg.pins);
+        assert_eq!(Format::Json, cfg.format);
+    }
+
+    #[test]
+    fn test_options_of_config_show() {
+        let cfg = Config::from_args(parse_args(["food", "-l", "3", "config", "show"]));
+        assert!(matches!(cfg.command, Command::ShowConfig));
+        assert!(show(&cfg.layers).contains("limit = 3 # command line\n"));
+
+        let cfg = Config::from_args(parse_args(["food", "config", "show", "-l", "4"]));
+        assert!(show(&cfg.layers).contains("limit = 4 # command line\n"));
+    }
+
+    #[test]
+    fn test_week() {
+        let sunday = NaiveDate::from_ymd_opt(2026, 10, 18);
+        assert_eq!(sunday, parse_week("2026-10-18"));
+        assert_eq!(sunday, parse_week("2026-10-24"));
+        assert_eq!(None, parse_week("2026-13-01"));
+        assert_eq!(Weekday::Sun, current_week().weekday());
+
+        let cfg = Config::from_args(parse_args(["food", "-S", "2900", "--week", "2026-10-21"]));
+        assert_eq!(sunday, cfg.week);
+        let cfg = Config::from_args(parse_args(["food", "-S", "2900"]));
+        assert_eq!(None, cfg.week);
+    }
+}
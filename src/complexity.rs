@@ -0,0 +1,163 @@
+use crate::recipe::Recipe;
+use std::str::FromStr;
+
+/// Minutes of cooking time that add one point to the complexity of a recipe
+const MINUTES_PER_POINT: u32 = 15;
+
+/// Number of levels of complexity that recipes are divided into when preferring varied recipes
+const LEVELS: usize = 3;
+
+/// Score how complex a recipe is to cook, where every ingredient and every instruction adds one
+/// point, and the total cooking time (when known) adds one point per 15 minutes
+pub fn complexity(recipe: &Recipe) -> u32 {
+    let time: u32 = recipe.time.unwrap_or(0) / MINUTES_PER_POINT;
+    (recipe.size() + recipe.steps) as u32 + time
+}
+
+/// Preferred complexity of the selected recipes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preference {
+    /// Prefer recipes which are no more complex than the median recipe
+    Simple,
+    /// Prefer a mix of simple, average and complex recipes
+    Varied,
+}
+
+impl FromStr for Preference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "simple" => Ok(Preference::Simple),
+            "varied" => Ok(Preference::Varied),
+            _ => Err(format!("Invalid preference: {}", s)),
+        }
+    }
+}
+
+/// Order the recipes according to the preference, while keeping the given order among recipes
+/// of similar complexity
+pub fn prefer(recipes: Vec<Recipe>, preference: Preference) -> Vec<Recipe> {
+    match preference {
+        Preference::Simple => simple_first(recipes),
+        Preference::Varied => varied(recipes),
+    }
+}
+
+fn simple_first(recipes: Vec<Recipe>) -> Vec<Recipe> {
+    let scores: Vec<u32> = recipes.iter().map(complexity).collect();
+    let median: u32 = match median(&scores) {
+        Some(median) => median,
+        None => return recipes,
+    };
+    log::debug!("Will partition on median complexity: {}", median);
+
+    let (under, over): (Vec<Recipe>, Vec<Recipe>) = recipes
+        .into_iter()
+        .partition(|r: &Recipe| complexity(r) <= median);
+
+    [under, over].concat()
+}
+
+/// Divide the recipes into levels of complexity, and take one recipe at a time from each level
+fn varied(recipes: Vec<Recipe>) -> Vec<Recipe> {
+    let len: usize = recipes.len();
+    let mut sorted: Vec<u32> = recipes.iter().map(complexity).collect();
+    sorted.sort_unstable();
+
+    let mut levels: Vec<Vec<Recipe>> = vec![Vec::new(); LEVELS];
+    for recipe in recipes.into_iter().rev() {
+        let rank: usize = sorted.partition_point(|score| *score < complexity(&recipe));
+        levels[rank * LEVELS / len].push(recipe);
+    }
+
+    let mut ordered: Vec<Recipe> = Vec::with_capacity(len);
+    while ordered.len() < len {
+        levels
+            .iter_mut()
+            .filter_map(|level: &mut Vec<Recipe>| level.pop())
+            .for_each(|recipe: Recipe| ordered.push(recipe));
+    }
+    ordered
+}
+
+fn median(list: &[u32]) -> Option<u32> {
+    let mut sorted: Vec<u32> = list.to_vec();
+    sorted.sort_unstable();
+    let len: usize = sorted.len();
+    let mid_index: usize = len / 2;
+    match len {
+        0 => None,
+        _ if len % 2 == 0 => Some((sorted[mid_index - 1] + sorted[mid_index]) / 2),
+        _ => Some(sorted[mid_index]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::complexity::{complexity, median, prefer, Preference};
+    use crate::recipe::{Ingredient, Recipe};
+
+    fn recipe(title: &str, ingredients: usize, steps: usize) -> Recipe {
+        let ingredients: Vec<Ingredient> = (0..ingredients)
+            .map(|i| Ingredient::parse(&format!("- item {}", i)).unwrap())
+            .collect();
+        let mut recipe = Recipe::new(title, ingredients, 4);
+        recipe.steps = steps;
+        recipe
+    }
+
+    fn names(recipes: &[Recipe]) -> Vec<&str> {
+        recipes.iter().map(|r| r.name()).collect()
+    }
+
+    #[test]
+    fn test_complexity() {
+        let mut recipe = recipe("Pancakes", 4, 3);
+        assert_eq!(7, complexity(&recipe));
+        recipe.time = Some(45);
+        assert_eq!(10, complexity(&recipe));
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(None, median(&[]));
+        assert_eq!(Some(4), median(&[4]));
+        assert_eq!(Some(3), median(&[9, 1, 3]));
+        assert_eq!(Some(5), median(&[9, 1, 4, 6]));
+    }
+
+    #[test]
+    fn test_prefer_simple() {
+        let recipes = vec![recipe("A", 10, 5), recipe("B", 2, 1), recipe("C", 3, 2)];
+        let recipes = prefer(recipes, Preference::Simple);
+        assert_eq!(vec!["B", "C", "A"], names(&recipes));
+        assert!(prefer(vec![], Preference::Simple).is_empty());
+    }
+
+    #[test]
+    fn test_prefer_varied() {
+        let recipes = vec![
+            recipe("Simple 1", 2, 1),
+            recipe("Simple 2", 2, 2),
+            recipe("Average 1", 5, 4),
+            recipe("Average 2", 5, 5),
+            recipe("Complex 1", 10, 8),
+            recipe("Complex 2", 12, 8),
+        ];
+        let recipes = prefer(recipes, Preference::Varied);
+        assert_eq!(
+            vec![
+                "Simple 1",
+                "Average 1",
+                "Complex 1",
+                "Simple 2",
+                "Average 2",
+                "Complex 2"
+            ],
+            names(&recipes)
+        );
+        assert!(prefer(vec![], Preference::Varied).is_empty());
+        assert_eq!(1, prefer(vec![recipe("A", 1, 1)], Preference::Varied).len());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::category::Categories;
+use crate::exclude::Exclusions;
+use crate::index::Index;
+use crate::nutrition::NutritionTable;
+use crate::overlap::Perishables;
+use crate::package::Packages;
+use crate::price::PriceList;
+use crate::rating::Ratings;
+use std::path::{Path, PathBuf};
+
+/// Name of the file in the [cache_dir] with the index of all recipes
+const INDEX_FILE: &str = "index";
+
+/// Directory for persistent, user specific files, such as the list of excluded ingredients. This
+/// is `$FOOD_CONFIG_DIR` if set, otherwise `$XDG_CONFIG_HOME/food` or `~/.config/food`.
+pub fn config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("FOOD_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    match (std::env::var("XDG_CONFIG_HOME"), std::env::var("HOME")) {
+        (Ok(config), _) => Some(PathBuf::from(config).join("food")),
+        (_, Ok(home)) => Some(PathBuf::from(home).join(".config").join("food")),
+        _ => None,
+    }
+}
+
+/// Directory for files that are generated and can be removed at any time, such as the index of
+/// recipes. This is `$FOOD_CACHE_DIR` if set, otherwise `$XDG_CACHE_HOME/food` or `~/.cache/food`.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("FOOD_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    match (std::env::var("XDG_CACHE_HOME"), std::env::var("HOME")) {
+        (Ok(cache), _) => Some(PathBuf::from(cache).join("food")),
+        (_, Ok(home)) => Some(PathBuf::from(home).join(".cache").join("food")),
+        _ => None,
+    }
+}
+
+/// Path to the file with the given name in the [config_dir], if there is such a file
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    config_dir()
+        .map(|dir: PathBuf| dir.join(name))
+        .filter(|file: &PathBuf| file.is_file())
+}
+
+/// Read `file` with `from_file`, or fall back to the default if there is no file, or if it can
+/// not be read, in which case a warning is logged
+fn load<T: Default>(file: Option<PathBuf>, from_file: fn(&Path) -> Result<T, String>) -> T {
+    match file.map(|file: PathBuf| from_file(&file)) {
+        Some(Ok(loaded)) => loaded,
+        Some(Err(e)) => {
+            log::warn!("{}", e);
+            T::default()
+        }
+        None => T::default(),
+    }
+}
+
+/// The ingredients and groups in `terms`, together with those in `exclude.md`
+pub fn load_exclusions(terms: &[String]) -> Exclusions {
+    let mut exclusions = Exclusions::new(terms);
+    exclusions.extend(load(config_file("exclude.md"), Exclusions::from_file));
+    exclusions
+}
+
+pub fn load_ratings() -> Ratings {
+    load(config_file("ratings.md"), Ratings::from_file)
+}
+
+pub fn load_perishables() -> Perishables {
+    load(config_file("perishables.md"), Perishables::from_file)
+}
+
+pub fn load_categories() -> Categories {
+    load(config_file("categories.md"), Categories::from_file)
+}
+
+pub fn load_packages() -> Packages {
+    load(config_file("packages.md"), Packages::from_file)
+}
+
+/// The price list in `file`, or in `prices.csv` if no file is given
+pub fn load_prices(file: Option<&str>) -> PriceList {
+    let file: Option<PathBuf> = match file {
+        Some(file) => Some(PathBuf::from(file)),
+        None => config_file("prices.csv"),
+    };
+    load(file, PriceList::from_file)
+}
+
+/// The nutrition table in `file`, or in `nutrition.csv` if no file is given, unless the table is
+/// empty
+pub fn load_nutrition(file: Option<&str>) -> Option<NutritionTable> {
+    let file: Option<PathBuf> = match file {
+        Some(file) => Some(PathBuf::from(file)),
+        None => config_file("nutrition.csv"),
+    };
+    Some(load(file, NutritionTable::from_file)).filter(|t: &NutritionTable| !t.is_empty())
+}
+
+/// Load the index from the [cache_dir], and update it with the files that have changed
+pub fn load_index(files: &[PathBuf]) -> Index {
+    let file: Option<PathBuf> = cache_dir().map(|dir: PathBuf| dir.join(INDEX_FILE));
+    let mut index: Index = load(file.clone(), Index::load);
+
+    let changed: usize = index.update(files);
+    log::info!(
+        "Updated {} files in the index, which has {} recipes",
+        changed,
+        index.len()
+    );
+    if let (Some(file), true) = (&file, changed > 0) {
+        if let Err(e) = index.save(file) {
+            log::warn!("{}", e);
+        }
+    }
+    index
+}
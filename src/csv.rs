@@ -0,0 +1,54 @@
+/// Split a line of CSV into its fields, where the fields are separated by semicolons if there is
+/// any semicolon outside of quotes, and by commas otherwise. Fields may be quoted, so that they
+/// can contain the separator, with `""` for a quote within a quoted field.
+pub fn split_fields(line: &str) -> Vec<String> {
+    let unquoted = |separator: char| {
+        line.chars()
+            .scan(false, |quoted, c| {
+                *quoted ^= c == '"';
+                Some(!*quoted && c == separator)
+            })
+            .any(|found| found)
+    };
+    let separator: char = if unquoted(';') { ';' } else { ',' };
+
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+        .iter()
+        .map(|f: &String| f.trim().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csv::split_fields;
+
+    #[test]
+    fn test_split_fields() {
+        assert_eq!(vec!["milk", "60", "3.5"], split_fields("milk, 60, 3.5"));
+        assert_eq!(vec!["milk", "3,5"], split_fields("milk;3,5"));
+        assert_eq!(
+            vec!["milk, whole", "3,5"],
+            split_fields("\"milk, whole\",\"3,5\"")
+        );
+        assert_eq!(
+            vec!["\"a;b\" milk", "1"],
+            split_fields("\"\"\"a;b\"\" milk\",1")
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use crate::args::Matches;
+use food::qty::Units;
+use food::shop::Format;
+use std::convert::TryInto;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Name of the config file in the config directory, see [food::config::config_dir]
+pub const USER_FILE: &str = "config.toml";
+
+/// Name of the config file of a project, which is read from the current directory
+pub const PROJECT_FILE: &str = ".food.toml";
+
+/// Default values of options, from a config file or environment variables. Each value is only
+/// set if it is given in that source, so that sources can be combined with [Defaults::or].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Defaults {
+    pub paths: Option<Vec<String>>,
+    pub limit: Option<usize>,
+    pub serving_size: Option<u8>,
+    pub simple: Option<bool>,
+    pub format: Option<Format>,
+    pub units: Option<Units>,
+    pub exclude: Option<Vec<String>>,
+}
+
+/// Defaults together with where they come from, such as the path to a config file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    pub origin: String,
+    pub defaults: Defaults,
+}
+
+impl Defaults {
+    /// Parse the content of a config file, such as
+    ///
+    /// ```toml
+    /// paths = ["~/recipes"]
+    /// limit = 5
+    /// serving-size = 4
+    /// simple = true
+    /// format = "markdown"
+    /// units = "imperial"
+    /// exclude = ["nuts", "cilantro"]
+    /// ```
+    pub fn parse(content: &str) -> Result<Defaults, String> {
+        let table = match content.parse::<Value>() {
+            Ok(Value::Table(table)) => table,
+            Ok(_) => return Err("Expected a table of options".to_string()),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let mut defaults = Defaults::default();
+        for (key, value) in table {
+            let invalid = |expected: &str| format!("Invalid {}, expected {}", key, expected);
+            match key.as_str() {
+                "paths" => defaults.paths = Some(strings(&value).ok_or_else(|| invalid("a list"))?),
+                "limit" => {
+                    let limit: Option<usize> = value.as_integer().and_then(|n| n.try_into().ok());
+                    defaults.limit = Some(limit.ok_or_else(|| invalid("a number"))?);
+                }
+                "serving-size" => {
+                    let size: Option<u8> = value.as_integer().and_then(|n| n.try_into().ok());
+                    defaults.serving_size = Some(size.ok_or_else(|| invalid("a number"))?);
+                }
+                "simple" => defaults.simple = Some(value.as_bool().ok_or_else(|| invalid("true"))?),
+                "format" => {
+                    let format: &str = value.as_str().ok_or_else(|| invalid("a string"))?;
+                    defaults.format = Some(format.parse()?);
+                }
+                "units" => {
+                    let units: &str = value.as_str().ok_or_else(|| invalid("a string"))?;
+                    defaults.units = Some(units.parse()?);
+                }
+                "exclude" => {
+                    defaults.exclude = Some(strings(&value).ok_or_else(|| invalid("a list"))?)
+                }
+                _ => return Err(format!("Unknown option '{}'", key)),
+            }
+        }
+        Ok(defaults)
+    }
+
+    /// Read the defaults from a config file, where paths starting with `~` are relative to the
+    /// home directory, and other relative paths are relative to the directory of the file
+    pub fn from_file(path: &Path) -> Result<Defaults, String> {
+        let content: String = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => return Err(format!("Unable to read config from {:?}: {}", path, e)),
+        };
+        let mut defaults: Defaults = match Defaults::parse(&content) {
+            Ok(defaults) => defaults,
+            Err(e) => return Err(format!("Invalid config in {:?}: {}", path, e)),
+        };
+        let dir: &Path = path.parent().unwrap_or_else(|| Path::new(""));
+        if let Some(paths) = &mut defaults.paths {
+            for p in paths.iter_mut() {
+                *p = resolve_path(p, dir);
+            }
+        }
+        Ok(defaults)
+    }
+
+    /// Read the defaults from the environment variables `FOOD_PATHS` (separated the same way as
+    /// `PATH`), `FOOD_LIMIT`, `FOOD_SERVING_SIZE`, `FOOD_SIMPLE`, `FOOD_FORMAT`, `FOOD_UNITS`
+    /// and `FOOD_EXCLUDE` (separated by commas)
+    pub fn from_env() -> Result<Defaults, String> {
+        Defaults::from_vars(|name: &str| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Defaults, String> {
+        fn parse<T>(name: &str, value: Option<String>) -> Result<Option<T>, String>
+        where
+            T: std::str::FromStr,
+        {
+            match value.map(|v| v.trim().parse::<T>()) {
+                None => Ok(None),
+                Some(Ok(value)) => Ok(Some(value)),
+                Some(Err(_)) => Err(format!("Invalid value of {}", name)),
+            }
+        }
+
+        let paths: Option<Vec<String>> = var("FOOD_PATHS").map(|paths: String| {
+            std::env::split_paths(&paths)
+                .map(|p: PathBuf| resolve_path(&p.to_string_lossy(), Path::new("")))
+                .collect()
+        });
+        let exclude: Option<Vec<String>> = var("FOOD_EXCLUDE").map(|exclude: String| {
+            exclude
+                .split(',')
+                .map(str::trim)
+                .filter(|x| !x.is_empty())
+                .map(str::to_string)
+                .collect()
+        });
+        let simple: Option<bool> = match var("FOOD_SIMPLE").as_deref().map(str::trim) {
+            None => None,
+            Some("1") | Some("true") => Some(true),
+            Some("0") | Some("false") => Some(false),
+            Some(_) => return Err("Invalid value of FOOD_SIMPLE, expected true or false".into()),
+        };
+
+        Ok(Defaults {
+            paths,
+            limit: parse("FOOD_LIMIT", var("FOOD_LIMIT"))?,
+            serving_size: parse("FOOD_SERVING_SIZE", var("FOOD_SERVING_SIZE"))?,
+            simple,
+            format: parse("FOOD_FORMAT", var("FOOD_FORMAT"))?,
+            units: parse("FOOD_UNITS", var("FOOD_UNITS"))?,
+            exclude,
+        })
+    }
+
+    /// The options given on the command line. If `explicit` is true, only options that were
+    /// actually given are set, otherwise the built-in default values of the options are used too.
+    pub fn from_args(args: &Matches, explicit: bool) -> Defaults {
+        let given = |name: &str| !explicit || args.occurrences_of(name) > 0;
+        let values = |name: &str| -> Vec<String> {
+            match args.values_of(name) {
+                Some(values) => values.map(|v| v.to_string()).collect(),
+                None => Vec::new(),
+            }
+        };
+
+        Defaults {
+            paths: Some(values("path")).filter(|_| given("path")),
+            limit: args
+                .value_of("limit")
+                .and_then(|n| n.parse().ok())
+                .filter(|_| given("limit")),
+            serving_size: args.value_of("serving_size").and_then(|n| n.parse().ok()),
+            simple: Some(args.is_present("simple")).filter(|_| given("simple")),
+            format: args
+                .value_of("format")
+                .and_then(|f| f.parse().ok())
+                .filter(|_| given("format")),
+            units: args
+                .value_of("units")
+                .and_then(|u| u.parse().ok())
+                .filter(|_| given("units")),
+            exclude: Some(values("exclude")).filter(|_| given("exclude")),
+        }
+    }
+
+    /// Combine the defaults with other defaults, where values in `self` take precedence
+    pub fn or(self, other: Defaults) -> Defaults {
+        Defaults {
+            paths: self.paths.or(other.paths),
+            limit: self.limit.or(other.limit),
+            serving_size: self.serving_size.or(other.serving_size),
+            simple: self.simple.or(other.simple),
+            format: self.format.or(other.format),
+            units: self.units.or(other.units),
+            exclude: self.exclude.or(other.exclude),
+        }
+    }
+}
+
+/// The sources of options, in order of precedence: the command line, environment variables, the
+/// config file of the project, the config file of the user, and the built-in defaults. Sources
+/// that could not be read are left out, and the reasons are returned as warnings.
+pub fn layers(args: &Matches, user_file: Option<PathBuf>) -> (Vec<Layer>, Vec<String>) {
+    let mut layers: Vec<Layer> = Vec::with_capacity(5);
+    let mut warnings: Vec<String> = Vec::new();
+    let mut add = |origin: String, defaults: Result<Defaults, String>| match defaults {
+        Ok(defaults) => layers.push(Layer { origin, defaults }),
+        Err(e) => warnings.push(e),
+    };
+
+    add(
+        "command line".to_string(),
+        Ok(Defaults::from_args(args, true)),
+    );
+    add("environment".to_string(), Defaults::from_env());
+    let project_file = Path::new(PROJECT_FILE);
+    if project_file.is_file() {
+        add(
+            format!("{}", project_file.display()),
+            Defaults::from_file(project_file),
+        );
+    }
+    if let Some(user_file) = user_file {
+        add(
+            format!("{}", user_file.display()),
+            Defaults::from_file(&user_file),
+        );
+    }
+    add("default".to_string(), Ok(Defaults::from_args(args, false)));
+    (layers, warnings)
+}
+
+/// Combine the defaults of all layers, in the order of the layers
+pub fn effective(layers: &[Layer]) -> Defaults {
+    layers
+        .iter()
+        .fold(Defaults::default(), |acc, layer: &Layer| {
+            acc.or(layer.defaults.clone())
+        })
+}
+
+/// The effective options as a config file, where each option is followed by a comment telling
+/// where the value comes from
+pub fn show(layers: &[Layer]) -> String {
+    let defaults: Defaults = effective(layers);
+    let origin = |is_set: fn(&Defaults) -> bool| -> &str {
+        layers
+            .iter()
+            .find(|layer: &&Layer| is_set(&layer.defaults))
+            .map(|layer: &Layer| layer.origin.as_str())
+            .unwrap_or("default")
+    };
+    let line = |key: &str, value: Option<Value>, is_set: fn(&Defaults) -> bool| match value {
+        Some(value) => format!("{} = {} # {}\n", key, value, origin(is_set)),
+        None => format!("# {} is not set\n", key),
+    };
+    let list = |values: Option<Vec<String>>| values.map(Value::from);
+
+    [
+        line("paths", list(defaults.paths), |d| d.paths.is_some()),
+        line(
+            "limit",
+            defaults.limit.map(|n| Value::from(n as i64)),
+            |d| d.limit.is_some(),
+        ),
+        line(
+            "serving-size",
+            defaults.serving_size.map(|n| Value::from(n as i64)),
+            |d| d.serving_size.is_some(),
+        ),
+        line("simple", defaults.simple.map(Value::from), |d| {
+            d.simple.is_some()
+        }),
+        line(
+            "format",
+            defaults.format.map(|f| Value::from(f.to_string())),
+            |d| d.format.is_some(),
+        ),
+        line(
+            "units",
+            defaults.units.map(|u| Value::from(u.to_string())),
+            |d| d.units.is_some(),
+        ),
+        line("exclude", list(defaults.exclude), |d| d.exclude.is_some()),
+    ]
+    .concat()
+}
+
+fn strings(value: &Value) -> Option<Vec<String>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|v: &Value| v.as_str().map(str::to_string))
+        .collect()
+}
+
+fn resolve_path(path: &str, dir: &Path) -> String {
+    let home: Option<String> = std::env::var("HOME").ok();
+    let path: PathBuf = match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            PathBuf::from(home).join(rest.trim_start_matches('/'))
+        }
+        _ => dir.join(path),
+    };
+    path.to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::defaults::{effective, show, Defaults, Layer};
+    use food::qty::Units;
+    use food::shop::Format;
+
+    #[test]
+    fn test_parse() {
+        let content = "paths = [\"recipes\"]\nlimit = 5\nserving-size = 4\nformat = \"md\"\n\
+                       units = \"imperial\"\nexclude = [\"nuts\"]";
+        let defaults: Defaults = Defaults::parse(content).unwrap();
+        assert_eq!(Some(vec!["recipes".to_string()]), defaults.paths);
+        assert_eq!(Some(5), defaults.limit);
+        assert_eq!(Some(4), defaults.serving_size);
+        assert_eq!(None, defaults.simple);
+        assert_eq!(Some(Format::Markdown), defaults.format);
+        assert_eq!(Some(Units::Imperial), defaults.units);
+        assert_eq!(Some(vec!["nuts".to_string()]), defaults.exclude);
+
+        assert!(Defaults::parse("limit = \"five\"").is_err());
+        assert!(Defaults::parse("serving-size = 300").is_err());
+        assert!(Defaults::parse("units = \"furlongs\"").is_err());
+        assert!(Defaults::parse("colour = \"blue\"").is_err());
+    }
+
+    #[test]
+    fn test_env() {
+        let vars = |name: &str| match name {
+            "FOOD_LIMIT" => Some("3".to_string()),
+            "FOOD_SIMPLE" => Some("true".to_string()),
+            "FOOD_EXCLUDE" => Some("nuts, dairy".to_string()),
+            "FOOD_UNITS" => Some("imperial".to_string()),
+            _ => None,
+        };
+        let defaults: Defaults = Defaults::from_vars(vars).unwrap();
+        assert_eq!(Some(3), defaults.limit);
+        assert_eq!(Some(true), defaults.simple);
+        assert_eq!(Some(Units::Imperial), defaults.units);
+        assert_eq!(
+            Some(vec!["nuts".to_string(), "dairy".to_string()]),
+            defaults.exclude
+        );
+        assert!(Defaults::from_vars(|_| Some("many".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_precedence() {
+        let layer = |origin: &str, content: &str| Layer {
+            origin: origin.to_string(),
+            defaults: Defaults::parse(content).unwrap(),
+        };
+        let layers = vec![
+            layer("command line", "serving-size = 2"),
+            layer("environment", "limit = 3"),
+            layer(".food.toml", "limit = 5\nsimple = true"),
+            layer("config.toml", "limit = 7\nsimple = false\nexclude = []"),
+            layer("default", "limit = 7\nsimple = false\nformat = \"text\""),
+        ];
+        let defaults: Defaults = effective(&layers);
+        assert_eq!(Some(3), defaults.limit);
+        assert_eq!(Some(true), defaults.simple);
+        assert_eq!(Some(2), defaults.serving_size);
+
+        let expected = "# paths is not set\nlimit = 3 # environment\n\
+                        serving-size = 2 # command line\nsimple = true # .food.toml\n\
+                        format = \"text\" # default\n# units is not set\n\
+                        exclude = [] # config.toml\n";
+        assert_eq!(expected, show(&layers));
+    }
+}
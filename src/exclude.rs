@@ -0,0 +1,229 @@
+use crate::recipe::{list_items, Ingredient, Recipe};
+use std::path::Path;
+
+/// Groups of ingredients which are commonly avoided together, such as allergens. Each group
+/// consists of a name, the ingredients in the group and ingredients that should _not_ be
+/// considered part of the group even though they share a name with one of its ingredients
+/// (coconut milk is not dairy).
+const GROUPS: [(&str, &[&str], &[&str]); 5] = [
+    (
+        "nuts",
+        &[
+            "nut",
+            "almond",
+            "cashew",
+            "hazelnut",
+            "macadamia",
+            "peanut",
+            "pecan",
+            "pine nut",
+            "pistachio",
+            "walnut",
+        ],
+        &[],
+    ),
+    (
+        "gluten",
+        &[
+            "wheat",
+            "barley",
+            "rye",
+            "spelt",
+            "bread",
+            "breadcrumb",
+            "bulgur",
+            "couscous",
+            "noodle",
+            "pasta",
+            "spaghetti",
+            "soy sauce",
+        ],
+        &["buckwheat", "rice noodle"],
+    ),
+    (
+        "dairy",
+        &[
+            "milk",
+            "butter",
+            "buttermilk",
+            "cheese",
+            "cream",
+            "creme fraiche",
+            "crème fraîche",
+            "yoghurt",
+            "yogurt",
+            "feta",
+            "mozzarella",
+            "parmesan",
+            "ricotta",
+            "mascarpone",
+        ],
+        &[
+            "coconut milk",
+            "coconut cream",
+            "oat milk",
+            "almond milk",
+            "soy milk",
+            "peanut butter",
+            "cocoa butter",
+        ],
+    ),
+    ("eggs", &["egg"], &["eggplant"]),
+    (
+        "shellfish",
+        &[
+            "shrimp", "prawn", "crab", "lobster", "crayfish", "mussel", "clam", "oyster", "scallop",
+        ],
+        &[],
+    ),
+];
+
+/// A list of ingredients, or groups of ingredients, that should not be part of any selected recipe
+#[derive(Debug, Clone, Default)]
+pub struct Exclusions {
+    terms: Vec<String>,
+}
+
+/// The reason for why a recipe was excluded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exclusion {
+    /// The ingredient in the recipe that caused the exclusion
+    pub item: String,
+    /// The excluded ingredient or group which the ingredient matched
+    pub term: String,
+}
+
+impl Exclusions {
+    pub fn new(terms: &[String]) -> Exclusions {
+        let terms: Vec<String> = terms.iter().map(|t| normalize(t)).collect();
+        Exclusions { terms }
+    }
+
+    /// Read exclusions from a file, where each excluded ingredient or group is listed on a line
+    /// of its own, preceded by a dash (like the ingredients in a recipe)
+    pub fn from_file(path: &Path) -> Result<Exclusions, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Exclusions::new(&list_items(&content))),
+            Err(e) => Err(format!("Unable to read exclusions from {:?}: {}", path, e)),
+        }
+    }
+
+    pub fn extend(&mut self, other: Exclusions) {
+        other
+            .terms
+            .into_iter()
+            .filter(|t| !t.is_empty())
+            .for_each(|t| self.terms.push(t))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Check if the recipe contains any excluded ingredient, returning the reason for the
+    /// exclusion if it does
+    pub fn check(&self, recipe: &Recipe) -> Option<Exclusion> {
+        recipe
+            .ingredients
+            .iter()
+            .find_map(|i: &Ingredient| self.check_item(&i.item))
+    }
+
+    pub fn check_item(&self, item: &str) -> Option<Exclusion> {
+        let item: String = normalize(item);
+        self.terms
+            .iter()
+            .find(|term| is_excluded_by(&item, term))
+            .map(|term| Exclusion {
+                item: item.clone(),
+                term: term.clone(),
+            })
+    }
+}
+
+fn is_excluded_by(item: &str, term: &str) -> bool {
+    match GROUPS.iter().find(|(name, _, _)| *name == term) {
+        Some((_, members, except)) => {
+            let is_exception: bool = except.iter().any(|e| contains_words(item, e));
+            !is_exception && members.iter().any(|m| contains_words(item, m))
+        }
+        None => contains_words(item, term),
+    }
+}
+
+/// Check if all the words in `term` are present in `item` in the same order, ignoring any
+/// differences in plural and singular form, so that "mushroom" matches "button mushrooms"
+pub fn contains_words(item: &str, term: &str) -> bool {
+    let item: Vec<&str> = item.split_whitespace().collect();
+    let term: Vec<&str> = term.split_whitespace().collect();
+    if term.is_empty() || term.len() > item.len() {
+        return false;
+    }
+    item.windows(term.len()).any(|window: &[&str]| {
+        window
+            .iter()
+            .zip(term.iter())
+            .all(|(w0, w1)| is_same_word(w0, w1))
+    })
+}
+
+fn is_same_word(w0: &str, w1: &str) -> bool {
+    let (short, long): (&str, &str) = if w0.len() <= w1.len() {
+        (w0, w1)
+    } else {
+        (w1, w0)
+    };
+    matches!(long.strip_prefix(short), Some("") | Some("s") | Some("es"))
+}
+
+fn normalize(term: &str) -> String {
+    term.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exclude::{contains_words, Exclusions};
+    use crate::recipe::{Ingredient, Recipe};
+
+    #[test]
+    fn test_contains_words_ignores_plural() {
+        assert!(contains_words("button mushrooms", "mushroom"));
+        assert!(contains_words("mushroom", "mushrooms"));
+        assert!(contains_words("chopped walnuts", "walnut"));
+        assert!(!contains_words("mushroom", "button mushroom"));
+        assert!(contains_words("grated cheeses", "cheese"));
+        assert!(contains_words("tomatoes", "tomato"));
+        assert!(!contains_words("nutmeg", "nut"));
+    }
+
+    #[test]
+    fn test_exclude_by_ingredient_name() {
+        let exclusions = Exclusions::new(&["Mushrooms".to_string()]);
+        let excluded = exclusions.check_item("button mushrooms").unwrap();
+        assert_eq!("mushrooms", excluded.term);
+        assert!(exclusions.check_item("onion").is_none());
+    }
+
+    #[test]
+    fn test_exclude_by_group() {
+        let exclusions = Exclusions::new(&["nuts".to_string(), "dairy".to_string()]);
+        assert!(exclusions.check_item("walnuts").is_some());
+        assert!(exclusions.check_item("salted peanuts").is_some());
+        assert!(exclusions.check_item("grated cheese").is_some());
+        assert!(exclusions.check_item("nutmeg").is_none());
+        assert!(exclusions.check_item("coconut milk").is_none());
+    }
+
+    #[test]
+    fn test_exclude_recipe() {
+        let ingredients: Vec<Ingredient> = vec![
+            Ingredient::parse(" - milk, 5 dl").unwrap(),
+            Ingredient::parse(" - eggs, 5").unwrap(),
+        ];
+        let recipe = Recipe::new("Pancakes", ingredients, 4u8);
+        let exclusions = Exclusions::new(&["eggs".to_string()]);
+        let excluded = exclusions.check(&recipe).unwrap();
+        assert_eq!("eggs", excluded.item);
+        assert!(Exclusions::new(&[]).check(&recipe).is_none());
+    }
+}
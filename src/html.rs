@@ -0,0 +1,123 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref HEADING: Regex = Regex::new(r"^(#{1,6})\s+(.*)$").unwrap();
+    static ref UNORDERED: Regex = Regex::new(r"^\s*[-*+]\s+(.*)$").unwrap();
+    static ref ORDERED: Regex = Regex::new(r"^\s*\d+\.\s+(.*)$").unwrap();
+    static ref STRONG: Regex = Regex::new(r"\*\*([^*]+)\*\*|__([^_]+)__").unwrap();
+    static ref EMPHASIS: Regex = Regex::new(r"\*([^*]+)\*|\b_([^_]+)_\b").unwrap();
+}
+
+/// Style of the HTML page, which is meant to be printed on paper as well as read on a screen
+pub const STYLE: &str = "\
+body { font-family: sans-serif; max-width: 50em; margin: 2em auto; padding: 0 1em; }
+h1 { font-size: 1.6em; border-bottom: 1px solid #999; }
+h2 { font-size: 1.2em; margin-bottom: 0.2em; }
+ul.checklist { list-style: none; padding-left: 0; columns: 2; }
+ul.checklist li { break-inside: avoid; padding: 0.1em 0; }
+section.category { break-inside: avoid; }
+article.recipe { border-top: 1px solid #999; margin-top: 2em; }
+@media print {
+  body { margin: 0; max-width: none; font-size: 11pt; }
+  a { color: inherit; text-decoration: none; }
+  article.recipe { break-before: page; border-top: none; margin-top: 0; }
+}";
+
+/// Escape the characters of `text` that have a special meaning in HTML
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Render the markdown of a recipe as HTML. Only the parts of markdown that are used in recipes
+/// are supported, which are headings, lists, paragraphs, and bold and italic text. Headings are
+/// made one level lower, so that the recipe fits under the headings of the page.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let mut html: Vec<String> = Vec::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut list: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let heading = HEADING.captures(line);
+        let ordered = ORDERED.captures(line);
+        let unordered = UNORDERED.captures(line);
+        let tag: Option<&str> = match (&ordered, &unordered) {
+            (Some(_), _) => Some("ol"),
+            (None, Some(_)) => Some("ul"),
+            (None, None) => None,
+        };
+
+        if line.trim().is_empty() || heading.is_some() || tag.is_some() {
+            end_paragraph(&mut html, &mut paragraph);
+        }
+        if list.is_some() && list != tag {
+            html.push(format!("</{}>", list.unwrap()));
+            list = None;
+        }
+
+        if let Some(heading) = heading {
+            let level: usize = (heading[1].len() + 1).min(6);
+            html.push(format!("<h{0}>{1}</h{0}>", level, inline(&heading[2])));
+        } else if let Some(item) = ordered.or(unordered) {
+            if list.is_none() {
+                html.push(format!("<{}>", tag.unwrap()));
+                list = tag;
+            }
+            html.push(format!("<li>{}</li>", inline(&item[1])));
+        } else if !line.trim().is_empty() {
+            paragraph.push(inline(line.trim()));
+        }
+    }
+
+    end_paragraph(&mut html, &mut paragraph);
+    if let Some(tag) = list {
+        html.push(format!("</{}>", tag));
+    }
+    html.join("\n")
+}
+
+fn end_paragraph(html: &mut Vec<String>, paragraph: &mut Vec<String>) {
+    if !paragraph.is_empty() {
+        html.push(format!("<p>{}</p>", paragraph.join(" ")));
+        paragraph.clear();
+    }
+}
+
+/// Escape the text and render bold and italic text
+fn inline(text: &str) -> String {
+    let text: String = escape(text);
+    let text = STRONG.replace_all(&text, "<strong>$1$2</strong>");
+    EMPHASIS.replace_all(&text, "<em>$1$2</em>").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::html::{escape, markdown_to_html};
+
+    #[test]
+    fn test_escape() {
+        assert_eq!("Mac &amp; cheese &lt;3", escape("Mac & cheese <3"));
+    }
+
+    #[test]
+    fn test_markdown_to_html() {
+        let markdown = "# Pancakes\nservings: 4\n\n- milk, 6 dl\n- eggs, 3\n\n\
+                        1. Whisk **well**\n2. Fry in *butter*\nServe hot.";
+        let expected = "<h2>Pancakes</h2>\n<p>servings: 4</p>\n\
+                        <ul>\n<li>milk, 6 dl</li>\n<li>eggs, 3</li>\n</ul>\n\
+                        <ol>\n<li>Whisk <strong>well</strong></li>\n\
+                        <li>Fry in <em>butter</em></li>\n</ol>\n<p>Serve hot.</p>";
+        assert_eq!(expected, markdown_to_html(markdown));
+    }
+}
@@ -0,0 +1,57 @@
+/// Escape the characters of `text` that have a special meaning in iCalendar text values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold the content line so that no line is longer than 75 bytes, as required by iCalendar,
+/// where every continuation line starts with a space. Lines end with CRLF.
+pub fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut length: usize = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ics::{escape, fold};
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            "milk\\, 6 dl\\nsalt\\; to taste",
+            escape("milk, 6 dl\nsalt; to taste")
+        );
+    }
+
+    #[test]
+    fn test_fold() {
+        assert_eq!("SUMMARY:Pancakes\r\n", fold("SUMMARY:Pancakes"));
+        let line: String = format!("DESCRIPTION:{}", "å".repeat(40));
+        let folded: String = fold(&line);
+        let lines: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(3, lines.len());
+        assert!(lines[0].len() <= 75);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(line, folded.replace("\r\n ", "").trim_end());
+    }
+}
@@ -0,0 +1,512 @@
+use crate::recipe::{Ingredient, Recipe};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// First line of the index file, with the version of its format. An index file with any other
+/// first line is ignored, and the index is rebuilt.
+const HEADER: &str = "food-index 1";
+
+/// Weight of a word in each part of a recipe, so that a word in the title counts more than a
+/// word in the instructions
+const TITLE_WEIGHT: u32 = 5;
+const TAG_WEIGHT: u32 = 4;
+const INGREDIENT_WEIGHT: u32 = 3;
+const TEXT_WEIGHT: u32 = 1;
+
+/// Common words that are not indexed, since they are found in almost every recipe
+const STOP_WORDS: [&str; 16] = [
+    "and", "the", "with", "for", "of", "in", "on", "to", "or", "an", "it", "is", "at", "by",
+    "into", "until",
+];
+
+/// Number of characters of context around the first match in a snippet
+const SNIPPET_CONTEXT: usize = 40;
+
+/// An inverted index over the titles, tags, ingredients and instructions of recipes, which maps
+/// every word to the recipes it is found in. The index is kept in a file, and only recipes that
+/// have been modified since they were indexed are read again when it is updated.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Index {
+    documents: BTreeMap<PathBuf, Document>,
+    /// The weight of each word in each recipe
+    postings: BTreeMap<String, BTreeMap<PathBuf, u32>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Document {
+    /// Time the file was modified, in nanoseconds since UNIX epoch
+    modified: u128,
+    /// Title of the recipe, or `None` for a file that is not a recipe, which is kept so that it
+    /// is not read again until it is modified
+    title: Option<String>,
+}
+
+/// A recipe that matches a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub path: PathBuf,
+    pub title: String,
+    /// Number of words in the query that are found in the recipe
+    pub matched: usize,
+    pub score: f64,
+}
+
+impl Index {
+    /// Read the index from `path`. A file that does not exist, or that was written by another
+    /// version, results in an empty index.
+    pub fn load(path: &Path) -> Result<Index, String> {
+        let content: String = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Index::default()),
+            Err(e) => return Err(format!("Unable to read index from {:?}: {}", path, e)),
+        };
+        match Index::parse(&content) {
+            Some(index) => Ok(index),
+            None => Err(format!("Invalid index in {:?}, it will be rebuilt", path)),
+        }
+    }
+
+    /// Parse the content of an index file, where each recipe is on a line `D`, followed by the
+    /// time it was modified, its path and its title (which is left out for a file that is not a
+    /// recipe), and each word is on a line `T`, followed by
+    /// the word and the number and weight of each recipe it is found in
+    fn parse(content: &str) -> Option<Index> {
+        let mut lines = content.lines();
+        if lines.next() != Some(HEADER) {
+            return Some(Index::default());
+        }
+
+        let mut index = Index::default();
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for line in lines {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields.as_slice() {
+                ["D", modified, path, title] => {
+                    let document = Document {
+                        modified: modified.parse().ok()?,
+                        title: Some(title.to_string()),
+                    };
+                    paths.push(PathBuf::from(path));
+                    index.documents.insert(PathBuf::from(path), document);
+                }
+                ["D", modified, path] => {
+                    let document = Document {
+                        modified: modified.parse().ok()?,
+                        title: None,
+                    };
+                    paths.push(PathBuf::from(path));
+                    index.documents.insert(PathBuf::from(path), document);
+                }
+                ["T", term, postings] => {
+                    let mut weights: BTreeMap<PathBuf, u32> = BTreeMap::new();
+                    for posting in postings.split(' ') {
+                        let (document, weight) = posting.split_once(':')?;
+                        let path: &PathBuf = paths.get(document.parse::<usize>().ok()?)?;
+                        weights.insert(path.clone(), weight.parse().ok()?);
+                    }
+                    index.postings.insert(term.to_string(), weights);
+                }
+                _ => return None,
+            }
+        }
+        Some(index)
+    }
+
+    /// Write the index to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let numbers: BTreeMap<&PathBuf, usize> = self
+            .documents
+            .keys()
+            .enumerate()
+            .map(|(n, p)| (p, n))
+            .collect();
+
+        let mut content: Vec<String> = vec![HEADER.to_string()];
+        for (path, document) in &self.documents {
+            let mut line: String = format!("D\t{}\t{}", document.modified, path.display());
+            if let Some(title) = &document.title {
+                line.push('\t');
+                line.push_str(&title.replace('\t', " "));
+            }
+            content.push(line);
+        }
+        for (term, weights) in &self.postings {
+            let postings: String = weights
+                .iter()
+                .map(|(path, weight)| format!("{}:{}", numbers[path], weight))
+                .join(" ");
+            content.push(format!("T\t{}\t{}", term, postings));
+        }
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Unable to create directory {:?}: {}", dir, e))?;
+        }
+        std::fs::write(path, content.join("\n") + "\n")
+            .map_err(|e| format!("Unable to write index to {:?}: {}", path, e))
+    }
+
+    /// Number of recipes in the index, not counting files that are not recipes
+    pub fn len(&self) -> usize {
+        self.documents
+            .values()
+            .filter(|d: &&Document| d.title.is_some())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index the files that are new or have been modified since they were indexed, and remove
+    /// recipes whose files no longer exist. Returns the number of files that were changed, which
+    /// is zero when the index is already up to date.
+    pub fn update(&mut self, files: &[PathBuf]) -> usize {
+        let mut changed: usize = 0;
+        for file in files {
+            let path: PathBuf = key(file);
+            let modified: u128 = match modified(&path) {
+                Some(modified) => modified,
+                None => continue,
+            };
+            if self.documents.get(&path).map(|d| d.modified) == Some(modified) {
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(content) => self.add(path, modified, &content),
+                Err(e) => {
+                    log::warn!("Unable to read recipe from {:?}: {}", path, e);
+                    continue;
+                }
+            }
+            changed += 1;
+        }
+
+        let removed: Vec<PathBuf> = self
+            .documents
+            .keys()
+            .filter(|p: &&PathBuf| !p.is_file())
+            .cloned()
+            .collect();
+        changed += removed.len();
+        removed.iter().for_each(|path: &PathBuf| self.remove(path));
+        changed
+    }
+
+    /// Add the recipe with the `content` to the index, replacing any previous version of it.
+    /// Content that is not a recipe is kept without a title or any words.
+    pub fn add(&mut self, path: PathBuf, modified: u128, content: &str) {
+        self.remove(&path);
+        let recipe: Recipe = match Recipe::parse(content) {
+            Some(recipe) => recipe,
+            None => {
+                let document = Document {
+                    modified,
+                    title: None,
+                };
+                self.documents.insert(path, document);
+                return;
+            }
+        };
+
+        let mut weights: BTreeMap<String, u32> = BTreeMap::new();
+        let mut add = |text: &str, weight: u32| {
+            for term in terms(text) {
+                *weights.entry(term).or_insert(0) += weight;
+            }
+        };
+        add(recipe.name(), TITLE_WEIGHT);
+        recipe
+            .tags
+            .iter()
+            .for_each(|tag: &String| add(tag, TAG_WEIGHT));
+        recipe
+            .ingredients
+            .iter()
+            .for_each(|i: &Ingredient| add(&i.item, INGREDIENT_WEIGHT));
+        add(content, TEXT_WEIGHT);
+
+        for (term, weight) in weights {
+            self.postings
+                .entry(term)
+                .or_default()
+                .insert(path.clone(), weight);
+        }
+        let document = Document {
+            modified,
+            title: Some(recipe.name().to_string()),
+        };
+        self.documents.insert(path, document);
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if self.documents.remove(path).is_none() {
+            return;
+        }
+        self.postings.values_mut().for_each(|weights| {
+            weights.remove(path);
+        });
+        self.postings.retain(|_, weights| !weights.is_empty());
+    }
+
+    /// Find the recipes among `files` that contain any word in the `query`. Recipes that contain
+    /// more of the words come first, and recipes that contain the same number of words are
+    /// ranked by how often, and where in the recipe, the words are found, where words that are
+    /// found in fewer recipes count more.
+    pub fn search(&self, query: &str, files: &[PathBuf]) -> Vec<Hit> {
+        let within: BTreeSet<PathBuf> = files.iter().map(|f: &PathBuf| key(f)).collect();
+        let total: f64 = within.len() as f64;
+        let mut hits: BTreeMap<&PathBuf, Hit> = BTreeMap::new();
+
+        for term in terms(query).iter().unique() {
+            let weights: Vec<(&PathBuf, &u32)> = match self.postings.get(term) {
+                Some(weights) => weights
+                    .iter()
+                    .filter(|(p, _)| within.contains(*p))
+                    .collect(),
+                None => continue,
+            };
+            let idf: f64 = (1.0 + total / weights.len().max(1) as f64).ln();
+            for (path, weight) in weights {
+                let hit: &mut Hit = hits.entry(path).or_insert_with(|| Hit {
+                    path: path.clone(),
+                    title: self.documents[path].title.clone().unwrap_or_default(),
+                    matched: 0,
+                    score: 0.0,
+                });
+                hit.matched += 1;
+                hit.score += *weight as f64 * idf;
+            }
+        }
+
+        hits.into_values()
+            .sorted_by(|a: &Hit, b: &Hit| {
+                b.matched
+                    .cmp(&a.matched)
+                    .then(b.score.total_cmp(&a.score))
+                    .then_with(|| a.title.cmp(&b.title))
+            })
+            .collect()
+    }
+}
+
+/// Split the text into the words that are indexed, which are lower case and without plural
+/// forms, so that "Tomatoes" is the same word as "tomato"
+pub fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word: &String| word.chars().count() > 1 && !STOP_WORDS.contains(&word.as_str()))
+        .map(|word: String| stem(&word))
+        .collect()
+}
+
+fn stem(word: &str) -> String {
+    let mut word: &str = word;
+    if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+        word = &word[..word.len() - 1];
+    }
+    if word.len() > 3 && word.ends_with('e') {
+        word = &word[..word.len() - 1];
+    }
+    word.to_string()
+}
+
+/// The part of the content around the first word (after the title) that matches the query,
+/// with every matching word surrounded by `open` and `close`. Returns `None` if no word matches.
+pub fn snippet(content: &str, query: &str, open: &str, close: &str) -> Option<String> {
+    let query: Vec<String> = terms(query);
+    let is_match = |word: &str| terms(word).first().map(|t| query.contains(t)) == Some(true);
+
+    // The lines after the title are searched first, so that the snippet shows more than the title
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let title: usize = lines
+        .iter()
+        .position(|l: &&str| l.starts_with('#'))
+        .unwrap_or(0);
+    let (line, start): (&str, usize) = lines
+        .get(title + 1..)?
+        .iter()
+        .chain(lines.get(..=title)?)
+        .find_map(|line: &&str| {
+        let word: Option<(usize, &str)> = tokens(line).into_iter().find(|(_, w)| is_match(w));
+        word.map(|(start, _)| (*line, start))
+    })?;
+
+    // Cut the line at whitespace, to not show parts of words
+    let mut from: usize = start.saturating_sub(SNIPPET_CONTEXT);
+    while !line.is_char_boundary(from) {
+        from -= 1;
+    }
+    if from > 0 {
+        from = line[from..start]
+            .find(' ')
+            .map(|n| from + n + 1)
+            .unwrap_or(start);
+    }
+    let mut to: usize = (start + 2 * SNIPPET_CONTEXT).min(line.len());
+    while !line.is_char_boundary(to) {
+        to += 1;
+    }
+    if to < line.len() {
+        to = line[start..to].rfind(' ').map(|n| start + n).unwrap_or(to);
+    }
+
+    let mut snippet = String::new();
+    if from > 0 {
+        snippet.push_str("...");
+    }
+    for (_, token) in tokens(&line[from..to]) {
+        if is_match(token) {
+            snippet.push_str(open);
+            snippet.push_str(token);
+            snippet.push_str(close);
+        } else {
+            snippet.push_str(token);
+        }
+    }
+    if to < line.len() {
+        snippet.push_str("...");
+    }
+    Some(snippet)
+}
+
+/// Split the text into words and the text between them, with the position of each part
+fn tokens(text: &str) -> Vec<(usize, &str)> {
+    let mut tokens: Vec<(usize, &str)> = Vec::new();
+    let mut start: usize = 0;
+    let mut in_word: Option<bool> = None;
+    for (i, c) in text.char_indices() {
+        let is_word: bool = c.is_alphanumeric();
+        if in_word.is_some() && in_word != Some(is_word) {
+            tokens.push((start, &text[start..i]));
+            start = i;
+        }
+        in_word = Some(is_word);
+    }
+    if start < text.len() {
+        tokens.push((start, &text[start..]));
+    }
+    tokens
+}
+
+/// The path that a file is kept under in the index, which is the canonical path if there is one
+fn key(file: &Path) -> PathBuf {
+    file.canonicalize().unwrap_or_else(|_| file.to_path_buf())
+}
+
+fn modified(path: &Path) -> Option<u128> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::{snippet, terms, Index};
+    use std::path::PathBuf;
+
+    const PASTA: &str = "---\ntags: quick\n---\n# Lemon pasta\nservings: 2\n- spaghetti, 200 g\n\
+                         - lemons, 2\n- parmesan, 50 g\n\n1. Boil the spaghetti.\n\
+                         2. Zest the lemon and mix it with grated parmesan and the pasta water.";
+    const CHICKEN: &str = "# Roast chicken\nservings: 4\n- chicken, 1\n- lemon, 1\n\n\
+                           1. Stuff the chicken with lemon.\n2. Roast for an hour.";
+    const SOUP: &str = "# Tomato soup\nservings: 4\n- tomatoes, 800 g\n\n1. Serve with pasta.";
+
+    fn index() -> (Index, Vec<PathBuf>) {
+        let mut index = Index::default();
+        let files: Vec<PathBuf> = vec!["pasta.md".into(), "chicken.md".into(), "soup.md".into()];
+        for (file, content) in files.iter().zip(&[PASTA, CHICKEN, SOUP]) {
+            index.add(file.clone(), 1, content);
+        }
+        (index, files)
+    }
+
+    #[test]
+    fn test_terms() {
+        assert_eq!(vec!["tomato", "chees"], terms("Tomatoes and a cheese"));
+        assert_eq!(terms("cheeses"), terms("cheese"));
+        assert_eq!(vec!["glass"], terms("glasses"));
+    }
+
+    #[test]
+    fn test_search() {
+        let (index, files) = index();
+        let titles = |query: &str| -> Vec<String> {
+            index
+                .search(query, &files)
+                .into_iter()
+                .map(|h| h.title)
+                .collect()
+        };
+        assert_eq!(vec!["Lemon pasta", "Tomato soup"], titles("pasta"));
+        assert_eq!(vec!["Lemon pasta", "Roast chicken"], titles("lemons"));
+        assert_eq!(
+            vec!["Roast chicken", "Lemon pasta"],
+            titles("chicken with lemon")
+        );
+        assert_eq!(vec!["Tomato soup"], titles("TOMATO"));
+        assert!(titles("quick").contains(&"Lemon pasta".to_string()));
+        assert!(titles("cucumber").is_empty());
+        assert!(index.search("pasta", &files[1..]).len() == 1);
+
+        let mut index: Index = index;
+        index.add("pasta.md".into(), 2, "# Pesto pasta\n- basil, 1 bunch");
+        assert!(index
+            .search("lemon", &files)
+            .iter()
+            .all(|h| h.title != "Pesto pasta"));
+        assert_eq!(3, index.len());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let (index, _) = index();
+        let dir: PathBuf = std::env::temp_dir().join(format!("food-index-{}", std::process::id()));
+        let file: PathBuf = dir.join("index");
+        index.save(&file).unwrap();
+        let loaded: Index = Index::load(&file).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(index, loaded);
+        assert!(Index::load(&file).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_update() {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("food-index-update-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files: Vec<PathBuf> = vec![dir.join("pasta.md"), dir.join("empty.md")];
+        std::fs::write(&files[0], PASTA).unwrap();
+        std::fs::write(&files[1], "").unwrap();
+
+        let mut index = Index::default();
+        assert_eq!(2, index.update(&files));
+        assert_eq!(1, index.len());
+        assert_eq!(0, index.update(&files));
+
+        let file: PathBuf = dir.join("index");
+        index.save(&file).unwrap();
+        let mut loaded: Index = Index::load(&file).unwrap();
+        assert_eq!(index, loaded);
+        assert_eq!(0, loaded.update(&files));
+
+        std::fs::remove_file(&files[1]).unwrap();
+        assert_eq!(1, loaded.update(&files[..1]));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(1, loaded.len());
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text: String = snippet(CHICKEN, "lemons", "[", "]").unwrap();
+        assert_eq!("- [lemon], 1", text);
+        let text: String = snippet(PASTA, "water", "*", "*").unwrap();
+        assert_eq!("...it with grated parmesan and the pasta *water*.", text);
+        assert_eq!(None, snippet(SOUP, "chicken", "[", "]"));
+    }
+}
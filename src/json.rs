@@ -0,0 +1,136 @@
+use std::fmt;
+
+/// A JSON value, which is printed as indented JSON. Object members are kept in the order they
+/// are added, so that the output is stable and easy to read.
+///
+/// This is used for the JSON output and the context of templates, rather than the `serde` feature
+/// and the `serialize` module, since their schema is versioned separately from the types of the
+/// library, with amounts in grams, milliliters or pieces and fields such as the category of each
+/// ingredient, and since they are available without the optional dependency on serde.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// An object with the given members
+    pub fn object(members: Vec<(&str, Json)>) -> Json {
+        let members: Vec<(String, Json)> = members
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        Json::Object(members)
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Number(n) if !n.is_finite() => write!(f, "null"),
+            Json::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => write!(f, "{}", *n as i64),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(values) if values.is_empty() => write!(f, "[]"),
+            Json::Array(values) => {
+                writeln!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    write!(f, "{:width$}", "", width = indent + 2)?;
+                    value.write(f, indent + 2)?;
+                    separator(f, i, values.len())?;
+                }
+                write!(f, "{:width$}]", "", width = indent)
+            }
+            Json::Object(members) if members.is_empty() => write!(f, "{{}}"),
+            Json::Object(members) => {
+                writeln!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    write!(f, "{:width$}", "", width = indent + 2)?;
+                    write_string(f, key)?;
+                    write!(f, ": ")?;
+                    value.write(f, indent + 2)?;
+                    separator(f, i, members.len())?;
+                }
+                write!(f, "{:width$}}}", "", width = indent)
+            }
+        }
+    }
+}
+
+fn separator(f: &mut fmt::Formatter<'_>, index: usize, len: usize) -> fmt::Result {
+    if index + 1 < len {
+        writeln!(f, ",")
+    } else {
+        writeln!(f)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Json {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<u32> for Json {
+    fn from(n: u32) -> Json {
+        Json::Number(n as f64)
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json {
+        match value {
+            Some(value) => value.into(),
+            None => Json::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::Json;
+
+    #[test]
+    fn test_escape_strings() {
+        let json = Json::from("Pasta \"al forno\"\n\\ 100%\u{1}");
+        assert_eq!(r#""Pasta \"al forno\"\n\\ 100%\u0001""#, json.to_string());
+    }
+
+    #[test]
+    fn test_nested_values() {
+        let json = Json::object(vec![
+            ("version", Json::from(1u32)),
+            ("servings", Json::from(None::<u32>)),
+            ("ratio", Json::Number(0.5)),
+            ("items", Json::Array(vec![Json::from("milk"), Json::Null])),
+            ("empty", Json::Array(Vec::new())),
+        ]);
+        let expected = "{\n  \"version\": 1,\n  \"servings\": null,\n  \"ratio\": 0.5,\n  \
+                        \"items\": [\n    \"milk\",\n    null\n  ],\n  \"empty\": []\n}";
+        assert_eq!(expected, json.to_string());
+    }
+}
@@ -0,0 +1,58 @@
+//! Library for generating shopping lists from recipes written in markdown.
+//!
+//! Recipes are parsed with [Recipe::parse] (or [Recipe::from_reader] and [Recipe::from_file]),
+//! scaled with [Recipe::apply_serving_size], and their ingredients are merged into a shopping
+//! list with [join_ingredients] or [merge]. Amounts are converted to readable units with
+//! [divide_unit], and recipes, ingredients and quantities are formatted with their `Display`
+//! implementations.
+//!
+//! ```
+//! use food::{divide_unit, join_ingredients, Recipe};
+//!
+//! let mut pancakes: Recipe = Recipe::parse("# Pancakes\nservings: 4\n- milk, 6 dl").unwrap();
+//! let porridge: Recipe = Recipe::parse("# Porridge\nservings: 2\n- milk, 2 dl").unwrap();
+//! pancakes.apply_serving_size(2);
+//!
+//! let list: Vec<String> = join_ingredients(vec![pancakes, porridge])
+//!     .iter()
+//!     .map(|i| divide_unit(i).to_string())
+//!     .collect();
+//! assert_eq!(vec!["milk, 5 dl"], list);
+//! ```
+//!
+//! Everything else that the `food` command line application does, such as selecting recipes,
+//! subtracting a pantry and printing the shopping list in different formats, is available in the
+//! other modules. The shopping list is rendered with [shop::render], and the files in the config
+//! directory are read with the functions in [config].
+
+pub mod category;
+pub mod complexity;
+pub mod config;
+pub(crate) mod csv;
+pub mod exclude;
+pub(crate) mod html;
+pub(crate) mod ics;
+pub mod index;
+pub(crate) mod json;
+pub mod lint;
+pub mod list;
+pub mod nutrition;
+pub mod overlap;
+pub mod package;
+pub mod pantry;
+pub mod price;
+pub mod qty;
+pub mod rating;
+pub mod recipe;
+pub(crate) mod report;
+pub mod search;
+pub mod select;
+#[cfg(feature = "serde")]
+pub mod serialize;
+pub mod shop;
+pub mod stats;
+pub mod target;
+pub(crate) mod template;
+
+pub use crate::qty::{Quantity, Volume, Weight};
+pub use crate::recipe::{divide_unit, join_ingredients, merge, Ingredient, Recipe, Source};
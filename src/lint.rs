@@ -0,0 +1,62 @@
+use crate::recipe::{Ingredient, Recipe, ITEM_PATTERN, SERVINGS_PATTERN};
+
+/// Find problems in the markdown `content` of a recipe, such as a missing title or ingredients
+/// that cannot be parsed. Each problem is described by a message, and an empty list means that
+/// the recipe is fine.
+pub fn lint(content: &str) -> Vec<String> {
+    let recipe: Recipe = match Recipe::parse(content) {
+        Some(recipe) if !recipe.name().is_empty() => recipe,
+        _ => return vec!["Missing title on the first line".to_string()],
+    };
+
+    let mut problems: Vec<String> = Vec::new();
+    if !recipe.title.starts_with('#') {
+        problems.push(format!("Title '{}' is not a heading", recipe.title));
+    }
+
+    for (index, line) in content.lines().enumerate() {
+        if SERVINGS_PATTERN.is_match(line) {
+            let servings: &str = line.rsplit(':').next().unwrap().trim();
+            if servings.parse::<u8>().is_err() {
+                problems.push(format!(
+                    "Invalid servings on line {}: {}",
+                    index + 1,
+                    servings
+                ));
+            }
+        } else if ITEM_PATTERN.is_match(line) {
+            if let Err(e) = Ingredient::parse(line) {
+                problems.push(format!("Invalid ingredient on line {}: {}", index + 1, e));
+            }
+        }
+    }
+
+    if recipe.ingredients.is_empty() {
+        problems.push("No ingredients".to_string());
+    }
+    if !content.lines().any(|line| SERVINGS_PATTERN.is_match(line)) {
+        problems.push("Missing servings, such as 'servings: 4'".to_string());
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::lint;
+
+    #[test]
+    fn test_lint() {
+        assert!(lint("# Pancakes\nservings: 4\n- milk, 6 dl\n- eggs, 3").is_empty());
+        assert_eq!(
+            vec!["Missing title on the first line"],
+            lint("\n- milk, 6 dl")
+        );
+
+        let problems: Vec<String> = lint("Pancakes\nservings: 400\n- milk, 6 dl, cold");
+        assert_eq!(4, problems.len());
+        assert_eq!("Title 'Pancakes' is not a heading", problems[0]);
+        assert_eq!("Invalid servings on line 2: 400", problems[1]);
+        assert!(problems[2].starts_with("Invalid ingredient on line 3"));
+        assert_eq!("No ingredients", problems[3]);
+    }
+}
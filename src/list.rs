@@ -0,0 +1,168 @@
+use crate::json::Json;
+use crate::recipe::Recipe;
+use crate::report::SCHEMA_VERSION;
+use itertools::Itertools;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// What the list of recipes is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sort {
+    #[default]
+    Title,
+    Path,
+    Servings,
+    /// Number of ingredients
+    Ingredients,
+}
+
+impl FromStr for Sort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "title" => Ok(Sort::Title),
+            "path" => Ok(Sort::Path),
+            "servings" => Ok(Sort::Servings),
+            "ingredients" => Ok(Sort::Ingredients),
+            _ => Err(format!("Invalid sort order: {}", s)),
+        }
+    }
+}
+
+/// Sort the recipes by `sort`, and then by title. Recipes without servings are sorted last when
+/// sorting by servings.
+pub fn sort(recipes: &mut [Recipe], sort: Sort) {
+    let title = |r: &Recipe| r.name().to_lowercase();
+    recipes.sort_by(|a: &Recipe, b: &Recipe| {
+        let order: Ordering = match sort {
+            Sort::Title => Ordering::Equal,
+            Sort::Path => a.path.cmp(&b.path),
+            Sort::Servings => match (a.servings, b.servings) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            },
+            Sort::Ingredients => a.size().cmp(&b.size()),
+        };
+        order.then_with(|| title(a).cmp(&title(b)))
+    });
+}
+
+/// The recipes as a table with the columns title, servings, ingredients, tags and path, where
+/// each column is as wide as its widest value
+pub fn to_table(recipes: &[Recipe]) -> String {
+    let header: Vec<String> = ["Title", "Servings", "Ingredients", "Tags", "Path"]
+        .iter()
+        .map(|h: &&str| h.to_string())
+        .collect();
+    let rows: Vec<Vec<String>> = recipes
+        .iter()
+        .map(|r: &Recipe| {
+            vec![
+                r.name().to_string(),
+                r.servings.map(|n| n.to_string()).unwrap_or_default(),
+                r.size().to_string(),
+                r.tags.join(", "),
+                r.path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    let rows: Vec<Vec<String>> = std::iter::once(header).chain(rows).collect();
+    let widths: Vec<usize> = (0..5)
+        .map(|column: usize| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap()
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row: &Vec<String>| {
+            row.iter()
+                .zip(&widths)
+                .map(|(value, width)| format!("{:width$}", value, width = width))
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .join("\n")
+}
+
+/// The recipes as a JSON document, with the same fields as the table
+pub fn to_json(recipes: &[Recipe]) -> String {
+    let recipes: Vec<Json> = recipes
+        .iter()
+        .map(|r: &Recipe| {
+            let path: Option<String> = r.path.as_ref().map(|p| p.to_string_lossy().to_string());
+            let tags: Vec<Json> = r
+                .tags
+                .iter()
+                .map(|t: &String| Json::from(t.as_str()))
+                .collect();
+            Json::object(vec![
+                ("title", Json::from(r.name())),
+                ("path", Json::from(path.as_deref())),
+                ("servings", Json::from(r.servings.map(u32::from))),
+                ("ingredients", Json::from(r.size() as u32)),
+                ("tags", Json::Array(tags)),
+            ])
+        })
+        .collect();
+
+    Json::object(vec![
+        ("version", Json::from(SCHEMA_VERSION)),
+        ("recipes", Json::Array(recipes)),
+    ])
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::list::{sort, to_json, to_table, Sort};
+    use crate::recipe::Recipe;
+
+    fn recipes() -> Vec<Recipe> {
+        vec![
+            "---\ntags: quick\n---\n# Porridge\nservings: 2\n- oats, 2 dl\n- milk, 4 dl",
+            "# Omelette\n- eggs, 2",
+            "# Pancakes\nservings: 4\n- milk, 6 dl\n- eggs, 3\n- wheat flour, 3 dl",
+        ]
+        .into_iter()
+        .map(|content| Recipe::parse(content).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut recipes: Vec<Recipe> = recipes();
+        let names = |recipes: &[Recipe]| -> Vec<String> {
+            recipes.iter().map(|r| r.name().to_string()).collect()
+        };
+
+        sort(&mut recipes, Sort::Title);
+        assert_eq!(vec!["Omelette", "Pancakes", "Porridge"], names(&recipes));
+        sort(&mut recipes, Sort::Servings);
+        assert_eq!(vec!["Porridge", "Pancakes", "Omelette"], names(&recipes));
+        sort(&mut recipes, Sort::Ingredients);
+        assert_eq!(vec!["Omelette", "Porridge", "Pancakes"], names(&recipes));
+        assert!("calories".parse::<Sort>().is_err());
+    }
+
+    #[test]
+    fn test_table_and_json() {
+        let expected = "Title     Servings  Ingredients  Tags   Path\n\
+                        Porridge  2         2            quick\n\
+                        Omelette            1";
+        assert_eq!(expected, to_table(&recipes()[..2]));
+
+        let json: String = to_json(&recipes()[..1]);
+        assert!(json.contains("\"title\": \"Porridge\""));
+        assert!(json.contains("\"ingredients\": 2"));
+        assert!(json.contains("\"tags\": [\n        \"quick\"\n      ]"));
+    }
+}
@@ -1,41 +1,73 @@
 #[macro_use]
 extern crate clap;
-extern crate lazy_static;
 mod args;
 mod cfg;
 mod dbg;
+mod defaults;
 mod logger;
-mod qty;
-mod recipe;
 
-use crate::cfg::Config;
+use crate::cfg::{current_week, Command, Config, Query};
 use crate::dbg::dbg_info;
+use crate::defaults::show;
 use crate::logger::setup_logging;
-use crate::recipe::{divide_unit, join_ingredients, Recipe};
+use food::config::{config_file, load_categories, load_exclusions, load_index, load_nutrition};
+use food::config::{load_packages, load_perishables, load_prices, load_ratings};
+use food::exclude::Exclusions;
+use food::index::{snippet, Hit, Index};
+use food::lint::lint;
+use food::list::{self, Sort};
+use food::overlap::overlap_score;
+use food::pantry::{Pantry, ShoppingList, Stock};
+use food::recipe::{convert_unit, join_ingredients, scale_content};
+use food::recipe::{Ingredient, Recipe, Source};
+use food::search::{search, Match};
+use food::select::{find_recipe, select_recipes, Criteria, Strategy};
+use food::shop::{render, Format, Options};
+use food::stats::Stats;
 use fwalker::Walker;
-use lazy_static::lazy_static;
+use itertools::Itertools;
 use rand::prelude::StdRng;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
-use regex::Regex;
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process;
 
-lazy_static! {
-    pub static ref ITEM_PATTERN: Regex = Regex::new(r"^\s*-\s+").unwrap();
-    pub static ref SERVINGS_PATTERN: Regex =
-        Regex::new(r"^\s*(servings|portioner):\s*\d+\s*$").unwrap();
-}
-
 fn main() {
     let cfg: Config = Config::from_args(args::args());
     setup_logging(cfg.verbosity_level);
 
+    for warning in &cfg.warnings {
+        log::warn!("{}", warning);
+    }
+
     if cfg.print_dbg {
         println!("{}", dbg_info());
         process::exit(0);
     }
 
+    if let Command::ShowConfig = cfg.command {
+        print!("{}", show(&cfg.layers));
+        process::exit(0);
+    }
+
+    let files: Vec<PathBuf> = find_files(&cfg);
+
+    match &cfg.command {
+        Command::Shop => shop(&cfg, files),
+        Command::List(sort, reverse) => list_recipes(&cfg, files, *sort, *reverse),
+        Command::Search(query) => search_recipes(files, query),
+        Command::Show(recipe) => show_recipe(&cfg, files, recipe),
+        Command::Scale(recipe, servings) => scale_recipe(files, recipe, *servings),
+        Command::Lint => lint_recipes(files),
+        Command::Stats => print_stats(files),
+        Command::Cook(recipe) => cook(&cfg, files, recipe),
+        Command::ConfirmPlan => confirm_plan(&cfg, files),
+        Command::ShowConfig => {}
+    }
+}
+
+fn find_files(cfg: &Config) -> Vec<PathBuf> {
     let (dirs, files): (Vec<PathBuf>, Vec<PathBuf>) = cfg
         .paths
         .iter()
@@ -46,78 +78,316 @@ fn main() {
     let found_files: Vec<PathBuf> = dirs
         .iter()
         .flat_map(|path: &PathBuf| Walker::from(path).unwrap())
-        .filter(accept_file_ext)
+        .filter(|f: &PathBuf| accept_file_ext(f))
         .filter(|f: &PathBuf| !f.ends_with("README.md"))
         .collect();
 
     let mut all_files: Vec<PathBuf> = [found_files, files].concat();
     let mut rand = StdRng::seed_from_u64(cfg.seed);
     all_files.shuffle(&mut rand);
+    all_files
+}
+
+/// Select recipes according to the configuration, scaled to the configured serving size
+fn select(cfg: &Config, files: Vec<PathBuf>) -> Vec<Recipe> {
+    let criteria = Criteria {
+        limit: cfg.limit,
+        seed: cfg.seed,
+        pins: cfg.pins.clone(),
+        preference: cfg.preference,
+        max_complexity: cfg.max_complexity,
+        strategy: cfg.strategy,
+        serving_size: cfg.serving_size,
+        budget: cfg.budget,
+        targets: cfg.targets.clone(),
+        exclusions: load_exclusions(&cfg.exclusions),
+        ratings: load_ratings(),
+        perishables: load_perishables(),
+        prices: load_prices(cfg.prices.as_deref()),
+        nutrition: load_nutrition(cfg.nutrition.as_deref()).unwrap_or_default(),
+    };
 
-    let mut recipes: Vec<Recipe> = select_recipes(all_files, cfg.limit, cfg.simple);
+    match select_recipes(files, &criteria) {
+        Ok(recipes) => recipes,
+        Err(e) => {
+            log::error!("{}", e);
+            process::exit(3);
+        }
+    }
+}
 
+fn scale(cfg: &Config, recipes: &mut [Recipe]) {
     if let Some(servings) = cfg.serving_size {
         recipes
             .iter_mut()
             .for_each(|r: &mut Recipe| r.apply_serving_size(servings));
     }
+}
 
-    let output = join_ingredients(recipes);
+fn shop(cfg: &Config, files: Vec<PathBuf>) {
+    let recipes: Vec<Recipe> = select(cfg, files);
+    let pantry: Pantry = match pantry_file(cfg) {
+        Some(file) => load_pantry(&file),
+        None => Pantry::default(),
+    };
+    let list: ShoppingList = pantry.subtract(join_ingredients(recipes.clone()));
+    for warning in &list.warnings {
+        log::warn!("{}", warning);
+    }
+    let overlap: Option<u32> = match cfg.strategy {
+        Strategy::Overlap => Some(overlap_score(&recipes, &load_perishables())),
+        Strategy::Random => None,
+    };
+    let options = Options {
+        format: cfg.format,
+        template: cfg.template.as_ref().map(PathBuf::from),
+        units: cfg.units,
+        group: cfg.group,
+        show_sources: cfg.show_sources,
+        package_choice: cfg.package_choice,
+        recipe_text: cfg.recipe_text,
+        week: cfg.week.unwrap_or_else(current_week),
+        dinner: cfg.dinner,
+        stamp: chrono::Utc::now().naive_utc(),
+        categories: load_categories(),
+        prices: load_prices(cfg.prices.as_deref()),
+        packages: load_packages(),
+        nutrition: load_nutrition(cfg.nutrition.as_deref()),
+        targets: cfg.targets.clone(),
+    };
 
-    output
-        .iter()
-        .map(divide_unit)
-        .for_each(|i| println!("{}", i))
+    match render(&recipes, &list, overlap, &options) {
+        Ok(output) => print!("{}", output),
+        Err(e) => {
+            log::error!("{}", e);
+            process::exit(5);
+        }
+    }
 }
 
-fn select_recipes(mut files: Vec<PathBuf>, limit: usize, only_simple: bool) -> Vec<Recipe> {
-    if only_simple {
-        let recipes: Vec<Recipe> = files
-            .iter()
-            .filter_map(|f| Recipe::from_file(f.to_path_buf()))
-            .inspect(|f| println!("{} => {}", f.title, f.size()))
-            .collect();
+/// Find the recipe with the given path or title among the files, or exit if there is none
+fn find(files: Vec<PathBuf>, key: &str) -> Recipe {
+    let candidates: Vec<Recipe> = files.into_iter().filter_map(Recipe::from_file).collect();
+    match find_recipe(&candidates, key) {
+        Some(recipe) => recipe,
+        None => {
+            log::error!("No recipe found for '{}'", key);
+            process::exit(3);
+        }
+    }
+}
 
-        let sizes: Vec<usize> = recipes.iter().map(|r: &Recipe| r.size()).collect();
-        let median_ingredients: usize = median(&sizes);
-        log::debug!("Will partition on median size: {}", median_ingredients);
+fn list_recipes(cfg: &Config, files: Vec<PathBuf>, sort: Sort, reverse: bool) {
+    let mut recipes: Vec<Recipe> = files.into_iter().filter_map(Recipe::from_file).collect();
+    list::sort(&mut recipes, sort);
+    if reverse {
+        recipes.reverse();
+    }
+    match cfg.format {
+        Format::Json => println!("{}", list::to_json(&recipes)),
+        _ => println!("{}", list::to_table(&recipes)),
+    }
+}
 
-        let (under, over): (Vec<_>, Vec<_>) = recipes
-            .iter()
-            .partition(|r: &&Recipe| r.size() <= median_ingredients);
+fn search_recipes(files: Vec<PathBuf>, query: &Query) {
+    let exclusions = Exclusions::new(&query.without);
+    let text: &str = match &query.text {
+        Some(text) => text,
+        None => {
+            let recipes: Vec<Recipe> = files.into_iter().filter_map(Recipe::from_file).collect();
+            let matches: Vec<Match> = search(&recipes, &query.with, &exclusions);
+            if matches.is_empty() {
+                log::error!("No recipe found");
+                process::exit(3);
+            }
+            matches.iter().for_each(|m: &Match| println!("{}", m));
+            return;
+        }
+    };
 
-        let recipes = [&under[..], &over[..]].concat();
+    let index: Index = load_index(&files);
+    let found: Vec<(Hit, String)> = index
+        .search(text, &files)
+        .into_iter()
+        .filter_map(|hit: Hit| {
+            let content: String = std::fs::read_to_string(&hit.path).ok()?;
+            let recipe: Recipe = Recipe::parse(&content)?;
+            exclusions
+                .check(&recipe)
+                .is_none()
+                .then_some((hit, content))
+        })
+        .take(query.max_results)
+        .collect();
+    if found.is_empty() {
+        log::error!("No recipe found for '{}'", text);
+        process::exit(3);
+    }
 
-        recipes
-            .iter()
-            .take(limit)
-            .inspect(|f| println!("{}", f))
-            .map(|r| r.to_owned().to_owned())
-            .collect()
+    // Matching words are shown in bold in a terminal, and as bold markdown otherwise
+    let (open, close): (&str, &str) = if std::io::stdout().is_terminal() {
+        ("\x1b[1m", "\x1b[0m")
     } else {
-        files
-            .iter_mut()
-            .take(limit)
-            .filter_map(|f| Recipe::from_file(f.to_path_buf()))
-            .inspect(|f| println!("{}", f))
-            .collect()
+        ("**", "**")
+    };
+    for (hit, content) in found {
+        println!("{}  {}", hit.title, hit.path.display());
+        if let Some(snippet) = snippet(&content, text, open, close) {
+            println!("    {}", snippet);
+        }
     }
 }
 
-fn median(list: &[usize]) -> usize {
-    let len: usize = list.len();
-    let mid_index: usize = len / 2;
-    if len % 2 == 0 {
-        let range = (mid_index - 1)..(mid_index + 1);
-        mean(&list[range])
-    } else {
-        list[mid_index]
+/// Print the details and the ingredients of a recipe, grouped by the component of the recipe
+/// they are listed under
+fn show_recipe(cfg: &Config, files: Vec<PathBuf>, key: &str) {
+    let mut recipe: Recipe = find(files, key);
+    scale(cfg, std::slice::from_mut(&mut recipe));
+
+    println!("{}", recipe.title);
+    if let Some(servings) = recipe.servings {
+        println!("servings: {}", servings);
+    }
+    if let Some(time) = recipe.time {
+        println!("time: {} min", time);
+    }
+    if let Some(rating) = recipe.rating {
+        println!("rating: {}", rating);
+    }
+
+    let component = |i: &Ingredient| i.sources.first().and_then(|s: &Source| s.component.clone());
+    for (component, ingredients) in &recipe.ingredients.iter().group_by(|i| component(i)) {
+        match component {
+            Some(component) => println!("\n## {}", component),
+            None => println!(),
+        }
+        for ingredient in ingredients {
+            println!(
+                "- {}",
+                convert_unit(ingredient, cfg.units).to_string().trim_end()
+            );
+        }
+    }
+
+    if let Some(path) = &recipe.path {
+        println!("\n{}", path.display());
+    }
+}
+
+/// Print the text of a recipe, scaled to the number of servings
+fn scale_recipe(files: Vec<PathBuf>, key: &str, servings: u8) {
+    let recipe: Recipe = find(files, key);
+    let text: String = recipe.text().unwrap_or_default();
+    match scale_content(&text, servings) {
+        Ok(scaled) => print!("{}", scaled),
+        Err(e) => {
+            log::error!("{}", e);
+            process::exit(3);
+        }
+    }
+}
+
+/// Print the problems of every recipe, and of titles that are used by several recipes
+fn lint_recipes(mut files: Vec<PathBuf>) {
+    files.sort();
+    let mut found: bool = false;
+    let mut titles: Vec<(String, PathBuf)> = Vec::new();
+    for file in files {
+        let content: String = match std::fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) => {
+                println!("{}: Unable to read file: {}", file.display(), e);
+                found = true;
+                continue;
+            }
+        };
+        for problem in lint(&content) {
+            println!("{}: {}", file.display(), problem);
+            found = true;
+        }
+        if let Some(recipe) = Recipe::parse(&content) {
+            titles.push((recipe.name().to_lowercase(), file));
+        }
+    }
+
+    titles.sort();
+    for (title, files) in &titles.iter().group_by(|(title, _)| title) {
+        let files: Vec<&PathBuf> = files.map(|(_, file)| file).collect();
+        if !title.is_empty() && files.len() > 1 {
+            let paths: String = files.iter().map(|f| f.display()).join(", ");
+            println!("Title '{}' is used by several recipes: {}", title, paths);
+            found = true;
+        }
+    }
+
+    if found {
+        process::exit(6);
     }
 }
 
-fn mean(list: &[usize]) -> usize {
-    let sum: usize = Iterator::sum(list.iter());
-    sum / list.len()
+fn print_stats(files: Vec<PathBuf>) {
+    let recipes: Vec<Recipe> = files.into_iter().filter_map(Recipe::from_file).collect();
+    print!("{}", Stats::new(&recipes));
+}
+
+fn cook(cfg: &Config, files: Vec<PathBuf>, key: &str) {
+    let mut recipe: Recipe = find(files, key);
+    scale(cfg, std::slice::from_mut(&mut recipe));
+    println!("{}", recipe);
+    consume(cfg, vec![recipe]);
+}
+
+fn confirm_plan(cfg: &Config, files: Vec<PathBuf>) {
+    let recipes: Vec<Recipe> = select(cfg, files);
+    recipes.iter().for_each(|r: &Recipe| println!("{}", r));
+    consume(cfg, recipes);
+}
+
+/// Remove the ingredients of the recipes from the pantry, and report all ingredients in the
+/// pantry that are below their minimum amount afterwards
+fn consume(cfg: &Config, recipes: Vec<Recipe>) {
+    let file: PathBuf = match pantry_file(cfg) {
+        Some(file) => file,
+        None => {
+            log::error!("No pantry found, see --pantry for how to use a pantry");
+            process::exit(4);
+        }
+    };
+
+    let mut pantry: Pantry = load_pantry(&file);
+    let ingredients: Vec<Ingredient> = join_ingredients(recipes);
+    let below: Vec<String> = pantry
+        .consume(&ingredients)
+        .iter()
+        .map(|s: &&Stock| s.to_string())
+        .collect();
+
+    if let Err(e) = pantry.write(&file) {
+        log::error!("{}", e);
+        process::exit(4);
+    }
+
+    if !below.is_empty() {
+        println!("\nBelow minimum, will be added to the next shopping list:");
+        below.iter().for_each(|s: &String| println!("{}", s));
+    }
+}
+
+fn pantry_file(cfg: &Config) -> Option<PathBuf> {
+    match &cfg.pantry {
+        Some(file) => Some(PathBuf::from(file)),
+        None => config_file("pantry.md"),
+    }
+}
+
+fn load_pantry(file: &Path) -> Pantry {
+    match Pantry::from_file(file) {
+        Ok(pantry) => pantry,
+        Err(e) => {
+            log::error!("{}", e);
+            process::exit(4);
+        }
+    }
 }
 
 fn check_path(path: &PathBuf) {
@@ -133,7 +403,7 @@ fn check_path(path: &PathBuf) {
 
 const ACCEPTED_EXTENSIONS: [&str; 2] = ["md", "txt"];
 
-fn accept_file_ext(path: &PathBuf) -> bool {
+fn accept_file_ext(path: &Path) -> bool {
     match path.extension() {
         Some(ext) => {
             let ext: &str = &ext.to_str().unwrap_or("").to_lowercase();
@@ -0,0 +1,259 @@
+use crate::csv::split_fields;
+use crate::exclude::contains_words;
+use crate::qty::Quantity;
+use crate::recipe::{Ingredient, Recipe};
+use std::fmt;
+use std::ops::{Add, Div};
+use std::path::Path;
+
+/// Energy and macronutrients, in kcal and grams
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Nutrients {
+    pub kcal: f64,
+    pub protein: f64,
+    pub fat: f64,
+    pub carbs: f64,
+}
+
+/// An entry in a nutrition table, with the nutrients of 100 g of the food
+#[derive(Debug, Clone, PartialEq)]
+pub struct Food {
+    pub name: String,
+    pub per_100g: Nutrients,
+    /// Weight in grams of one milliliter, needed for ingredients measured by volume
+    pub density: Option<f64>,
+    /// Weight in grams of one piece, needed for ingredients counted in pieces
+    pub piece_weight: Option<f64>,
+}
+
+/// Nutritional values of foods
+#[derive(Debug, Clone, Default)]
+pub struct NutritionTable {
+    foods: Vec<Food>,
+}
+
+/// The nutrients of a recipe
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecipeNutrition {
+    pub total: Nutrients,
+    /// Nutrients per serving, if the number of servings of the recipe is known
+    pub per_serving: Option<Nutrients>,
+    /// Ingredients that could not be found in the nutrition table, or converted to grams, and which
+    /// therefore are not part of the nutrients
+    pub missing: Vec<String>,
+}
+
+impl NutritionTable {
+    /// Parse a nutrition table in CSV format, with the columns name, kcal, protein, fat and carbs
+    /// per 100 g, optionally followed by density (g/ml) and piece weight (g). Columns may be
+    /// separated by semicolons instead of commas, in which case decimal commas are allowed, as in
+    /// many exports of national food composition databases. Fields that contain the separator can
+    /// be quoted. The first line may be a header, and empty lines and lines starting with `#` are
+    /// allowed.
+    pub fn parse(content: &str) -> Result<NutritionTable, String> {
+        let mut foods: Vec<Food> = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line: &str = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<String> = split_fields(line);
+
+            if fields.len() < 5 || fields.len() > 7 {
+                let line: usize = index + 1;
+                return Err(format!(
+                    "Expected 5 to 7 columns on line {} of nutrition table",
+                    line
+                ));
+            }
+
+            let number = |i: usize| -> Result<Option<f64>, String> {
+                match fields.get(i).map(|f| f.replace(',', ".")) {
+                    None => Ok(None),
+                    Some(f) if f.is_empty() => Ok(None),
+                    Some(f) => match f.parse::<f64>() {
+                        Ok(n) if n >= 0.0 => Ok(Some(n)),
+                        _ => Err(format!("Invalid number on line {}: {}", index + 1, f)),
+                    },
+                }
+            };
+
+            let kcal: f64 = match number(1) {
+                Ok(Some(kcal)) => kcal,
+                Err(_) if index == 0 => continue,
+                Ok(None) => return Err(format!("Missing kcal on line {}", index + 1)),
+                Err(e) => return Err(e),
+            };
+
+            let per_100g = Nutrients {
+                kcal,
+                protein: number(2)?.unwrap_or(0.0),
+                fat: number(3)?.unwrap_or(0.0),
+                carbs: number(4)?.unwrap_or(0.0),
+            };
+
+            foods.push(Food {
+                name: fields[0].to_lowercase(),
+                per_100g,
+                density: number(5)?,
+                piece_weight: number(6)?,
+            });
+        }
+        Ok(NutritionTable { foods })
+    }
+
+    pub fn from_file(path: &Path) -> Result<NutritionTable, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => NutritionTable::parse(&content),
+            Err(e) => Err(format!(
+                "Unable to read nutrition table from {:?}: {}",
+                path, e
+            )),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.foods.is_empty()
+    }
+
+    /// Find the food for the ingredient, which is either an exact match or the food with the
+    /// longest name matching the words of the ingredient, so that "red onions" matches "onion"
+    pub fn get(&self, item: &str) -> Option<&Food> {
+        match self.foods.iter().find(|f: &&Food| f.name == item) {
+            Some(food) => Some(food),
+            None => self
+                .foods
+                .iter()
+                .filter(|f: &&Food| contains_words(item, &f.name))
+                .max_by_key(|f: &&Food| f.name.len()),
+        }
+    }
+
+    /// The nutrients of the ingredient, if it is in the table and its amount can be converted to
+    /// grams
+    pub fn ingredient_nutrients(&self, ingredient: &Ingredient) -> Option<Nutrients> {
+        let food: &Food = self.get(&ingredient.item)?;
+        let grams: f64 = grams(&ingredient.amount, food)?;
+        Some(food.per_100g.scale(grams / 100.0))
+    }
+
+    pub fn recipe_nutrition(&self, recipe: &Recipe) -> RecipeNutrition {
+        let mut nutrition = RecipeNutrition::default();
+        for ingredient in &recipe.ingredients {
+            match self.ingredient_nutrients(ingredient) {
+                Some(nutrients) => nutrition.total = nutrition.total + nutrients,
+                None => nutrition.missing.push(ingredient.item.clone()),
+            }
+        }
+        nutrition.per_serving = match recipe.servings {
+            Some(servings) if servings > 0 => Some(nutrition.total / servings as f64),
+            _ => None,
+        };
+        nutrition
+    }
+}
+
+/// Convert the quantity to grams, using the density of the food for volumes and the weight of a
+/// piece for pieces
+fn grams(quantity: &Quantity, food: &Food) -> Option<f64> {
+    match quantity {
+        Quantity::Weight(w) => Some(w.as_grams() as f64),
+        Quantity::Volume(v) => food.density.map(|d| v.as_milliliters() as f64 * d),
+        Quantity::Pieces(n) => food.piece_weight.map(|w| *n as f64 * w),
+        Quantity::Custom(_, _) => None,
+    }
+}
+
+impl Nutrients {
+    pub fn scale(&self, factor: f64) -> Nutrients {
+        Nutrients {
+            kcal: self.kcal * factor,
+            protein: self.protein * factor,
+            fat: self.fat * factor,
+            carbs: self.carbs * factor,
+        }
+    }
+}
+
+impl Add for Nutrients {
+    type Output = Nutrients;
+
+    fn add(self, other: Nutrients) -> Nutrients {
+        Nutrients {
+            kcal: self.kcal + other.kcal,
+            protein: self.protein + other.protein,
+            fat: self.fat + other.fat,
+            carbs: self.carbs + other.carbs,
+        }
+    }
+}
+
+impl Div<f64> for Nutrients {
+    type Output = Nutrients;
+
+    fn div(self, rhs: f64) -> Nutrients {
+        self.scale(1.0 / rhs)
+    }
+}
+
+impl fmt::Display for Nutrients {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.0} kcal, {:.0} g protein, {:.0} g fat, {:.0} g carbs",
+            self.kcal, self.protein, self.fat, self.carbs
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nutrition::NutritionTable;
+    use crate::recipe::{Ingredient, Recipe};
+
+    const TABLE: &str = "name,kcal,protein,fat,carbs,density,piece weight\n\
+                         wheat flour,340,10,1,70,0.6\n\
+                         milk,60,3.5,3,5,1.03\n\
+                         egg,140,12,10,0.5,,60\n\
+                         butter,740,0.5,82,0.5\n";
+
+    #[test]
+    fn test_parse_semicolons_and_decimal_commas() {
+        let content = "Namn;kcal;Protein;Fett;Kolhydrater\nMjölk;60;3,5;3;5";
+        let table = NutritionTable::parse(content).unwrap();
+        assert_eq!(3.5, table.get("mjölk").unwrap().per_100g.protein);
+        assert!(NutritionTable::parse("milk,60,3.5,3,5\nbutter,lots,0,0,0").is_err());
+        let error = NutritionTable::parse("# Per 100 g\nbutter,lots,0,0,0\nmilk,60,3.5,3,5");
+        assert_eq!("Invalid number on line 2: lots", error.unwrap_err());
+        let table = NutritionTable::parse("\"tomatoes, canned\",20,\"1,2\",0.2,3").unwrap();
+        assert_eq!(1.2, table.get("tomatoes, canned").unwrap().per_100g.protein);
+    }
+
+    #[test]
+    fn test_ingredient_nutrients() {
+        let table = NutritionTable::parse(TABLE).unwrap();
+        let eggs = table
+            .ingredient_nutrients(&Ingredient::parse("- eggs, 2").unwrap())
+            .unwrap();
+        assert!((eggs.kcal - 168.0).abs() < 0.01);
+        let flour = table.ingredient_nutrients(&Ingredient::parse("- wheat flour, 1 dl").unwrap());
+        assert!((flour.unwrap().kcal - 204.0).abs() < 0.01);
+        let butter = Ingredient::parse("- butter, 1 tbsp").unwrap();
+        assert!(table.ingredient_nutrients(&butter).is_none());
+    }
+
+    #[test]
+    fn test_recipe_nutrition() {
+        let table = NutritionTable::parse(TABLE).unwrap();
+        let ingredients: Vec<Ingredient> = vec![
+            Ingredient::parse("- milk, 5 dl").unwrap(),
+            Ingredient::parse("- eggs, 2").unwrap(),
+            Ingredient::parse("- salt, 1 tsp").unwrap(),
+        ];
+        let nutrition = table.recipe_nutrition(&Recipe::new("Pancakes", ingredients, 2));
+        assert!((nutrition.total.kcal - (309.0 + 168.0)).abs() < 0.01);
+        assert!((nutrition.per_serving.unwrap().kcal - 238.5).abs() < 0.01);
+        assert_eq!(vec!["salt".to_string()], nutrition.missing);
+    }
+}
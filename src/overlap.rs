@@ -0,0 +1,187 @@
+use crate::exclude::contains_words;
+use crate::recipe::{list_items, Ingredient, Recipe};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// How much more a shared perishable ingredient is worth compared to other shared ingredients
+const PERISHABLE_WEIGHT: u32 = 3;
+
+/// How many candidates per recipe to select that will be considered when looking for recipes that
+/// share ingredients. A larger value gives more overlap, but less variation between selections.
+const CANDIDATES_PER_RECIPE: usize = 3;
+
+/// Ingredients which do not keep well, and which therefore are extra important to use in more than
+/// one recipe when bought
+#[derive(Debug, Clone, Default)]
+pub struct Perishables {
+    items: Vec<String>,
+}
+
+impl Perishables {
+    pub fn new(items: &[String]) -> Perishables {
+        let items: Vec<String> = items.iter().map(|i| i.trim().to_lowercase()).collect();
+        Perishables { items }
+    }
+
+    /// Read perishable ingredients from a file, where each ingredient is listed on a line of its
+    /// own, preceded by a dash
+    pub fn from_file(path: &Path) -> Result<Perishables, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Perishables::new(&list_items(&content))),
+            Err(e) => Err(format!("Unable to read perishables from {:?}: {}", path, e)),
+        }
+    }
+
+    pub fn is_perishable(&self, item: &str) -> bool {
+        self.items.iter().any(|p| contains_words(item, p))
+    }
+
+    fn weight(&self, item: &str) -> u32 {
+        if self.is_perishable(item) {
+            PERISHABLE_WEIGHT
+        } else {
+            1
+        }
+    }
+}
+
+/// Score how much the recipes overlap in their ingredients, where each ingredient used in more than
+/// one recipe adds to the score for every additional recipe it is used in. Ingredients are
+/// compared the same way as when merged into a shopping list.
+pub fn overlap_score(recipes: &[Recipe], perishables: &Perishables) -> u32 {
+    count_items(recipes)
+        .iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(item, count)| (count - 1) * perishables.weight(item))
+        .sum()
+}
+
+/// Ingredients used in more than one of the recipes, with the number of recipes using them
+pub fn shared_items(recipes: &[Recipe]) -> Vec<(String, u32)> {
+    let mut shared: Vec<(String, u32)> = count_items(recipes)
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .collect();
+    shared.sort();
+    shared
+}
+
+/// Select `limit` recipes from `candidates` to add to the already `selected` recipes, preferring
+/// recipes that share as many ingredients as possible with the recipes selected so far. The order
+/// of the candidates is kept when recipes are equally good, and only the first few candidates
+/// are considered so that the selection still varies with the order of the candidates.
+pub fn select_overlapping(
+    selected: &[Recipe],
+    candidates: Vec<Recipe>,
+    limit: usize,
+    perishables: &Perishables,
+) -> Vec<Recipe> {
+    let mut window: Vec<Recipe> = candidates
+        .into_iter()
+        .take(limit.saturating_mul(CANDIDATES_PER_RECIPE))
+        .collect();
+
+    let mut items: HashSet<String> = selected.iter().flat_map(items_of).collect();
+    let mut chosen: Vec<Recipe> = Vec::with_capacity(limit.min(window.len()));
+
+    while chosen.len() < limit && !window.is_empty() {
+        let gain = |recipe: &Recipe| -> u32 {
+            items_of(recipe)
+                .iter()
+                .filter(|item| items.contains(*item))
+                .map(|item| perishables.weight(item))
+                .sum()
+        };
+        let best: usize = window
+            .iter()
+            .enumerate()
+            .max_by(|(i0, r0), (i1, r1)| gain(r0).cmp(&gain(r1)).then(i1.cmp(i0)))
+            .map(|(index, _)| index)
+            .unwrap();
+
+        let recipe: Recipe = window.remove(best);
+        items.extend(items_of(&recipe));
+        chosen.push(recipe);
+    }
+
+    chosen
+}
+
+fn items_of(recipe: &Recipe) -> HashSet<String> {
+    recipe
+        .ingredients
+        .iter()
+        .map(|i: &Ingredient| i.item.clone())
+        .collect()
+}
+
+fn count_items(recipes: &[Recipe]) -> HashMap<String, u32> {
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    recipes
+        .iter()
+        .flat_map(items_of)
+        .for_each(|item| *counts.entry(item).or_insert(0) += 1);
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::overlap::{overlap_score, select_overlapping, Perishables};
+    use crate::recipe::{Ingredient, Recipe};
+
+    fn recipe(title: &str, items: &[&str]) -> Recipe {
+        let ingredients: Vec<Ingredient> = items
+            .iter()
+            .map(|i| Ingredient::parse(i).unwrap())
+            .collect();
+        Recipe::new(title, ingredients, 4)
+    }
+
+    #[test]
+    fn test_overlap_score() {
+        let recipes = vec![
+            recipe(
+                "Curry",
+                &["- coriander, 1 bunch", "- onion, 2", "- rice, 4 dl"],
+            ),
+            recipe("Tacos", &["- coriander, 1 bunch", "- onion, 1"]),
+            recipe("Soup", &["- onion, 1"]),
+        ];
+        assert_eq!(3, overlap_score(&recipes, &Perishables::default()));
+        let perishables = Perishables::new(&["coriander".to_string()]);
+        assert_eq!(5, overlap_score(&recipes, &perishables));
+    }
+
+    #[test]
+    fn test_select_overlapping_prefers_shared_ingredients() {
+        let selected = vec![recipe("Curry", &["- coriander", "- onion", "- rice"])];
+        let candidates = vec![
+            recipe("Pancakes", &["- milk", "- eggs"]),
+            recipe("Tacos", &["- coriander", "- onion"]),
+            recipe("Soup", &["- onion"]),
+        ];
+        let chosen = select_overlapping(&selected, candidates, 2, &Perishables::default());
+        let titles: Vec<&str> = chosen.iter().map(|r| r.name()).collect();
+        assert_eq!(vec!["Tacos", "Soup"], titles);
+    }
+
+    #[test]
+    fn test_select_overlapping_keeps_order_when_equal() {
+        let candidates = vec![
+            recipe("Pancakes", &["- milk", "- eggs"]),
+            recipe("Tacos", &["- coriander", "- onion"]),
+        ];
+        let chosen = select_overlapping(&[], candidates, 1, &Perishables::default());
+        assert_eq!("Pancakes", chosen[0].name());
+    }
+
+    #[test]
+    fn test_select_overlapping_without_limit() {
+        let candidates = vec![
+            recipe("Pancakes", &["- milk", "- eggs"]),
+            recipe("Tacos", &["- coriander", "- onion"]),
+        ];
+        let chosen = select_overlapping(&[], candidates, usize::MAX, &Perishables::default());
+        assert_eq!(2, chosen.len());
+    }
+}
@@ -0,0 +1,331 @@
+use crate::price::parse_money;
+use crate::qty::Quantity;
+use crate::recipe::{divide_unit, list_items, Ingredient};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// How to choose among the combinations of packages that cover the amount needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Choice {
+    /// Choose the combination with the lowest price, when all packages of an ingredient have a
+    /// price, otherwise the combination with the fewest packages
+    #[default]
+    Cheapest,
+    /// Choose the combination with the fewest packages
+    Fewest,
+}
+
+impl FromStr for Choice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cheapest" => Ok(Choice::Cheapest),
+            "fewest" => Ok(Choice::Fewest),
+            _ => Err(format!("Invalid package choice: {}", s)),
+        }
+    }
+}
+
+/// A size that an ingredient can be bought in, such as a 400 g can
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub size: Quantity,
+    /// Name of the package, such as "can" or "carton"
+    pub name: Option<String>,
+    /// Price of the package in cents (or the smallest unit of any other currency)
+    pub price: Option<u32>,
+}
+
+/// The sizes that ingredients can be bought in
+#[derive(Debug, Clone, Default)]
+pub struct Packages {
+    items: Vec<(String, Vec<Package>)>,
+}
+
+/// An ingredient rounded up to whole packages
+#[derive(Debug, Clone, PartialEq)]
+pub struct Purchase {
+    pub item: String,
+    /// The packages to buy, with the number of each package
+    pub packages: Vec<(u32, Package)>,
+    /// How much more than needed that is bought
+    pub surplus: Quantity,
+}
+
+impl Packages {
+    /// Parse packages where each ingredient is listed on a line of its own, preceded by a dash and
+    /// followed by the sizes it can be bought in, such as `- cream, 25 cl carton, 5 dl carton`.
+    /// Each size may have a name and end with a price, such as `400 g can 12.90`.
+    pub fn parse(content: &str) -> Result<Packages, String> {
+        let items: Vec<(String, Vec<Package>)> = list_items(content)
+            .iter()
+            .map(|line: &String| {
+                let mut parts = line.split(',').map(str::trim);
+                let item: String = parts.next().unwrap().to_lowercase();
+                let packages: Vec<Package> = parts
+                    .map(Package::parse)
+                    .collect::<Result<Vec<Package>, String>>()?;
+                if packages.is_empty() {
+                    return Err(format!("No package sizes given for '{}'", item));
+                }
+                Ok((item, packages))
+            })
+            .collect::<Result<Vec<(String, Vec<Package>)>, String>>()?;
+
+        Ok(Packages { items })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Packages, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Packages::parse(&content),
+            Err(e) => Err(format!("Unable to read packages from {:?}: {}", path, e)),
+        }
+    }
+
+    /// Add a package size of the ingredient
+    pub fn insert(&mut self, item: &str, package: Package) {
+        match self.items.iter_mut().find(|(name, _)| name == item) {
+            Some((_, packages)) => packages.push(package),
+            None => self.items.push((item.to_string(), vec![package])),
+        }
+    }
+
+    /// Add the package sizes of all ingredients in `other` which are not already listed
+    pub fn extend(&mut self, other: Packages) {
+        for (item, packages) in other.items {
+            if self.get(&item).is_none() {
+                self.items.push((item, packages));
+            }
+        }
+    }
+
+    pub fn get(&self, item: &str) -> Option<&[Package]> {
+        self.items
+            .iter()
+            .find(|(name, _)| name == item)
+            .map(|(_, packages)| packages.as_slice())
+    }
+
+    /// Round the amount of the ingredient up to whole packages, if the ingredient is available in
+    /// packages of a size that can be converted to the unit of the ingredient
+    pub fn round(&self, ingredient: &Ingredient, choice: Choice) -> Option<Purchase> {
+        let packages: Vec<&Package> = self
+            .get(&ingredient.item)?
+            .iter()
+            .filter(|p: &&Package| p.size.is_same_kind(&ingredient.amount))
+            .filter(|p: &&Package| !p.size.is_zero())
+            .collect();
+
+        if packages.is_empty() {
+            log::debug!(
+                "No package size of {} matches {}",
+                ingredient.item,
+                ingredient.amount
+            );
+            return None;
+        }
+
+        let priced: bool = packages.iter().all(|p: &&Package| p.price.is_some());
+        let choice: Choice = match choice {
+            Choice::Cheapest if !priced => Choice::Fewest,
+            _ => choice,
+        };
+
+        let need: u32 = ingredient.amount.base_amount();
+        let sizes: Vec<u32> = packages.iter().map(|p| p.size.base_amount()).collect();
+        let prices: Vec<u32> = packages.iter().map(|p| p.price.unwrap_or(0)).collect();
+        let counts: Vec<u32> = combine(need, &sizes, &prices, choice);
+
+        let total: u32 = counts.iter().zip(&sizes).map(|(n, size)| n * size).sum();
+        let mut bought: Vec<(u32, Package)> = counts
+            .into_iter()
+            .zip(packages)
+            .filter(|(n, _)| *n > 0)
+            .map(|(n, p)| (n, p.clone()))
+            .collect();
+        bought.sort_by_key(|(_, p)| std::cmp::Reverse(p.size.base_amount()));
+
+        Some(Purchase {
+            item: ingredient.item.clone(),
+            packages: bought,
+            surplus: ingredient.amount.with_base_amount(total - need),
+        })
+    }
+}
+
+/// Find the number of each package size needed to cover `need`, choosing the cheapest or the
+/// fewest packages, and the least surplus among equally good combinations
+fn combine(need: u32, sizes: &[u32], prices: &[u32], choice: Choice) -> Vec<u32> {
+    let unit: u32 = sizes.iter().fold(0, |acc, size| gcd(acc, *size));
+    let sizes: Vec<usize> = sizes.iter().map(|size| (size / unit) as usize).collect();
+    let need: usize = ((need + unit - 1) / unit) as usize;
+    let upper: usize = need + sizes.iter().max().unwrap();
+
+    let rank = |(price, count): (u64, u64)| match choice {
+        Choice::Cheapest => (price, count),
+        Choice::Fewest => (count, 0),
+    };
+
+    // Best way of buying exactly `amount`, as (price, number of packages) and last package added
+    let mut best: Vec<Option<((u64, u64), usize)>> = vec![None; upper + 1];
+    best[0] = Some(((0, 0), 0));
+    for amount in 1..=upper {
+        best[amount] = sizes
+            .iter()
+            .enumerate()
+            .filter(|(_, size)| **size <= amount)
+            .filter_map(|(i, size)| {
+                let ((price, count), _) = best[amount - size]?;
+                Some(((price + prices[i] as u64, count + 1), i))
+            })
+            .min_by_key(|(cost, i)| (rank(*cost), *i));
+    }
+
+    let mut amount: usize = (need..=upper)
+        .filter(|amount| best[*amount].is_some())
+        .min_by_key(|amount| (rank(best[*amount].unwrap().0), *amount))
+        .unwrap();
+
+    let mut counts: Vec<u32> = vec![0; sizes.len()];
+    while amount > 0 {
+        let (_, i) = best[amount].unwrap();
+        counts[i] += 1;
+        amount -= sizes[i];
+    }
+    counts
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+impl Package {
+    fn parse(input: &str) -> Result<Package, String> {
+        let mut words: Vec<&str> = input.split_whitespace().collect();
+        let price: Option<u32> = match words.split_last() {
+            Some((last, rest)) if !rest.is_empty() => parse_price(last),
+            _ => None,
+        };
+        if price.is_some() {
+            words.pop();
+        }
+
+        let (size, name): (Quantity, &[&str]) = match words.as_slice() {
+            [] => return Err(format!("Missing package size in '{}'", input)),
+            [number] => (Quantity::parse(number)?, &[]),
+            [number, unit, name @ ..] => match Quantity::parse(&format!("{} {}", number, unit))? {
+                Quantity::Custom(_, _) => (Quantity::parse(number)?, &words[1..]),
+                size => (size, name),
+            },
+        };
+
+        let name: Option<String> = match name {
+            [] => None,
+            name => Some(name.join(" ")),
+        };
+
+        Ok(Package { size, name, price })
+    }
+}
+
+/// Parse a price such as `12.90` into cents, where the decimals are required to distinguish the
+/// price from the name of the package
+fn parse_price(input: &str) -> Option<u32> {
+    match input.contains('.') {
+        true => parse_money(input),
+        false => None,
+    }
+}
+
+impl fmt::Display for Package {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size: String = self.size.to_string();
+        match &self.name {
+            Some(name) => write!(f, "{} {}", size.trim(), name),
+            None => write!(f, "{}", size.trim()),
+        }
+    }
+}
+
+impl fmt::Display for Purchase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let packages: Vec<String> = self
+            .packages
+            .iter()
+            .map(|(n, package)| format!("{} × {}", n, package))
+            .collect();
+        write!(f, "{}, {}", self.item, packages.join(" + "))?;
+        if !self.surplus.is_zero() {
+            let surplus = divide_unit(&Ingredient::new(self.item.clone(), self.surplus.clone()));
+            write!(f, " ({} extra)", surplus.amount.to_string().trim())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::package::{Choice, Packages};
+    use crate::qty::{Quantity, Volume, Weight};
+    use crate::recipe::Ingredient;
+
+    #[test]
+    fn test_parse_packages() {
+        let content = "- Cream, 25 cl carton 9.50, 5 dl carton\n- eggs, 12";
+        let packages = Packages::parse(content).unwrap();
+        let cream = packages.get("cream").unwrap();
+        assert_eq!(Quantity::Volume(Volume::Centiliter(25)), cream[0].size);
+        assert_eq!(Some("carton".to_string()), cream[0].name);
+        assert_eq!(Some(950), cream[0].price);
+        assert_eq!(None, cream[1].price);
+        assert_eq!(Quantity::Pieces(12), packages.get("eggs").unwrap()[0].size);
+        assert!(Packages::parse("- cream").is_err());
+    }
+
+    #[test]
+    fn test_round_to_fewest_packages() {
+        let packages = Packages::parse("- canned tomatoes, 400 g can").unwrap();
+        let tomatoes = Ingredient::parse("- canned tomatoes, 1 kg").unwrap();
+        let purchase = packages.round(&tomatoes, Choice::Fewest).unwrap();
+        assert_eq!(3, purchase.packages[0].0);
+        assert_eq!(Quantity::Weight(Weight::Gram(200)), purchase.surplus);
+        assert_eq!(
+            "canned tomatoes, 3 × 400 g can (200 g extra)",
+            purchase.to_string()
+        );
+
+        let packages = Packages::parse("- cream, 25 cl carton, 5 dl carton").unwrap();
+        let cream = Ingredient::parse("- cream, 7 dl").unwrap();
+        let purchase = packages.round(&cream, Choice::Fewest).unwrap();
+        assert_eq!(
+            "cream, 1 × 5 dl carton + 1 × 25 cl carton (5 cl extra)",
+            purchase.to_string()
+        );
+    }
+
+    #[test]
+    fn test_round_to_cheapest_packages() {
+        let packages = Packages::parse("- cream, 25 cl carton 10.00, 5 dl carton 25.00").unwrap();
+        let cream = Ingredient::parse("- cream, 5 dl").unwrap();
+        let cheapest = packages.round(&cream, Choice::Cheapest).unwrap();
+        assert_eq!("cream, 2 × 25 cl carton", cheapest.to_string());
+        let fewest = packages.round(&cream, Choice::Fewest).unwrap();
+        assert_eq!("cream, 1 × 5 dl carton", fewest.to_string());
+    }
+
+    #[test]
+    fn test_no_matching_package() {
+        let packages = Packages::parse("- milk, 1 l").unwrap();
+        assert!(packages
+            .round(&Ingredient::parse("- milk, 2").unwrap(), Choice::Fewest)
+            .is_none());
+        assert!(packages
+            .round(&Ingredient::parse("- eggs, 2").unwrap(), Choice::Fewest)
+            .is_none());
+    }
+}
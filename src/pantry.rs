@@ -0,0 +1,377 @@
+use crate::qty::Quantity;
+use crate::recipe::{divide_unit, list_items, Ingredient};
+use std::path::Path;
+
+/// Ingredients which are already at home, and therefore do not need to be bought
+#[derive(Debug, Clone, Default)]
+pub struct Pantry {
+    stock: Vec<Stock>,
+}
+
+/// An ingredient in the pantry
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stock {
+    pub item: String,
+    /// The amount on hand, or `None` for staples which are always assumed to be in stock
+    pub amount: Option<Quantity>,
+    /// The amount that should always be on hand. Whenever there is less than this, the missing
+    /// amount is added to the shopping list.
+    pub minimum: Option<Quantity>,
+}
+
+/// A shopping list with the contents of the pantry taken into account
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShoppingList {
+    /// Ingredients that need to be bought
+    pub to_buy: Vec<Ingredient>,
+    /// Ingredients needed that are staples, and which are assumed to be in stock
+    pub staples: Vec<Ingredient>,
+    /// Ingredients whose amount could not be compared with the amount in the pantry, such as a
+    /// volume of an ingredient that is kept by weight, to be reported to the user. All of such
+    /// an ingredient is bought.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub warnings: Vec<String>,
+}
+
+impl Pantry {
+    /// Parse a pantry, where each ingredient is listed on a line of its own, preceded by a dash.
+    /// An ingredient may have a quantity, in the same way as an ingredient in a recipe, which is
+    /// the amount that is on hand. An ingredient without a quantity is a staple, which is always
+    /// assumed to be in stock. The quantity may be followed by a minimum amount to keep on hand,
+    /// such as `- milk, 2 l, min 1 l`.
+    pub fn parse(content: &str) -> Result<Pantry, String> {
+        let stock: Vec<Stock> = list_items(content)
+            .iter()
+            .map(|line: &String| Stock::parse(line))
+            .collect::<Result<Vec<Stock>, String>>()?;
+
+        Ok(Pantry { stock })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Pantry, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Pantry::parse(&content),
+            Err(e) => Err(format!("Unable to read pantry from {:?}: {}", path, e)),
+        }
+    }
+
+    /// Remove the (merged) ingredients from the pantry, and get all ingredients in the pantry that
+    /// are below their minimum amount afterwards. Staples, and ingredients that are not in the
+    /// pantry, are not affected.
+    pub fn consume(&mut self, ingredients: &[Ingredient]) -> Vec<&Stock> {
+        for ingredient in ingredients {
+            let found = self.stock.iter_mut().find(|s| s.item == ingredient.item);
+            let stock: &mut Stock = match found {
+                Some(stock) => stock,
+                None => continue,
+            };
+
+            let rest: Option<Quantity> = match &stock.amount {
+                Some(amount) => amount.checked_sub(&ingredient.amount),
+                None => continue,
+            };
+
+            match rest {
+                Some(rest) => stock.amount = Some(rest),
+                None => log::warn!(
+                    "Cannot remove {} of {} from the pantry, which has {}",
+                    ingredient.amount,
+                    ingredient.item,
+                    stock.amount.as_ref().unwrap()
+                ),
+            }
+        }
+
+        self.stock.iter().filter(|s| s.is_below_minimum()).collect()
+    }
+
+    /// Write the pantry to `path`, where all ingredients already listed in the file are updated
+    /// with the current amounts and all other lines of the file are kept as is
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let content: String = std::fs::read_to_string(path).unwrap_or_default();
+        let mut written: Vec<&str> = Vec::with_capacity(self.stock.len());
+        let mut lines: Vec<String> = content
+            .lines()
+            .map(|line: &str| match list_items(line).first() {
+                Some(entry) => match Stock::parse(entry).ok().and_then(|s| self.get(&s.item)) {
+                    Some(stock) => {
+                        written.push(&stock.item);
+                        let prefix: &str = &line[..line.len() - line.trim_start().len()];
+                        format!("{}- {}", prefix, stock)
+                    }
+                    None => line.to_string(),
+                },
+                None => line.to_string(),
+            })
+            .collect();
+
+        self.stock
+            .iter()
+            .filter(|s: &&Stock| !written.contains(&s.item.as_str()))
+            .for_each(|s: &Stock| lines.push(format!("- {}", s)));
+
+        let mut content: String = lines.join("\n");
+        content.push('\n');
+        std::fs::write(path, content)
+            .map_err(|e| format!("Unable to write pantry to {:?}: {}", path, e))
+    }
+
+    pub fn get(&self, item: &str) -> Option<&Stock> {
+        self.stock.iter().find(|s: &&Stock| s.item == item)
+    }
+
+    /// Subtract what is on hand in the pantry from the (merged) ingredients. Ingredients that are
+    /// fully covered by the pantry are removed, and staples are listed separately. Ingredients
+    /// that would drop below their minimum amount in the pantry are added to the list. Amounts
+    /// that are not of the same kind as in the pantry are added to the list as they are, with a
+    /// warning in [ShoppingList::warnings].
+    pub fn subtract(&self, ingredients: Vec<Ingredient>) -> ShoppingList {
+        let restock: Vec<Ingredient> = self
+            .stock
+            .iter()
+            .filter(|s: &&Stock| !ingredients.iter().any(|i| i.item == s.item))
+            .filter_map(|s: &Stock| {
+                let min: Quantity = s.minimum.clone()?;
+                Some(Ingredient::new(s.item.clone(), min))
+            })
+            .collect();
+
+        let mut list = ShoppingList::default();
+        let needed: Vec<Ingredient> = ingredients
+            .into_iter()
+            .map(|i: Ingredient| self.with_minimum(i, &mut list.warnings))
+            .chain(restock)
+            .collect();
+
+        for ingredient in needed {
+            let stock: &Stock = match self.get(&ingredient.item) {
+                Some(stock) => stock,
+                None => {
+                    list.to_buy.push(ingredient);
+                    continue;
+                }
+            };
+
+            let on_hand: &Quantity = match &stock.amount {
+                Some(amount) => amount,
+                None => {
+                    list.staples.push(ingredient);
+                    continue;
+                }
+            };
+
+            match ingredient.amount.checked_sub(on_hand) {
+                Some(rest) if rest.is_zero() => {
+                    log::debug!("{} is covered by the pantry", ingredient.item)
+                }
+                Some(rest) => list.to_buy.push(Ingredient {
+                    amount: rest,
+                    ..ingredient
+                }),
+                None => {
+                    list.warnings.push(format!(
+                        "Cannot subtract {} of {} in the pantry from {}, so all of it is bought",
+                        on_hand, ingredient.item, ingredient.amount
+                    ));
+                    list.to_buy.push(ingredient)
+                }
+            }
+        }
+        list
+    }
+
+    /// Add the minimum amount to keep in the pantry, if any, to the amount of the ingredient. The
+    /// minimum is left out, with a warning, if it cannot be added to the amount of the ingredient.
+    fn with_minimum(&self, ingredient: Ingredient, warnings: &mut Vec<String>) -> Ingredient {
+        match self
+            .get(&ingredient.item)
+            .and_then(|s: &Stock| s.minimum.clone())
+        {
+            Some(min) if ingredient.amount.is_same_kind(&min) => Ingredient {
+                amount: ingredient.amount + min,
+                ..ingredient
+            },
+            Some(min) => {
+                warnings.push(format!(
+                    "Cannot add the minimum amount {} of {} in the pantry to {}",
+                    min, ingredient.item, ingredient.amount
+                ));
+                ingredient
+            }
+            None => ingredient,
+        }
+    }
+}
+
+impl Stock {
+    fn parse(line: &str) -> Result<Stock, String> {
+        let parts: Vec<&str> = line.split(',').map(str::trim).collect();
+        let item: String = parts[0].to_lowercase();
+        let (amount, minimum): (Option<Quantity>, Option<Quantity>) = match parts.len() {
+            1 => (None, None),
+            2 => (Some(parse_amount(parts[1])?), None),
+            3 => match parts[2].strip_prefix("min") {
+                Some(min) => (
+                    Some(parse_amount(parts[1])?),
+                    Some(Quantity::parse(min.trim())?),
+                ),
+                None => return Err(format!("Expected minimum amount in pantry '{}'", line)),
+            },
+            _ => return Err(format!("Invalid line in pantry '{}'", line)),
+        };
+        if let (Some(amount), Some(min)) = (&amount, &minimum) {
+            if !amount.is_same_kind(min) {
+                return Err(format!(
+                    "Minimum amount {} does not match the unit of {} in pantry '{}'",
+                    min, amount, line
+                ));
+            }
+        }
+        Ok(Stock {
+            item,
+            amount,
+            minimum,
+        })
+    }
+
+    /// Check if there is less than the minimum amount of this ingredient on hand
+    pub fn is_below_minimum(&self) -> bool {
+        match (&self.amount, &self.minimum) {
+            (Some(amount), Some(min)) => match min.checked_sub(amount) {
+                Some(missing) => !missing.is_zero(),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Stock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let readable = |q: &Quantity| -> String {
+            let ingredient = Ingredient::new(self.item.clone(), q.clone());
+            let q: Quantity = if q.is_zero() {
+                q.clone()
+            } else {
+                divide_unit(&ingredient).amount
+            };
+            q.to_string().trim().to_string()
+        };
+        match (&self.amount, &self.minimum) {
+            (Some(amount), Some(min)) => {
+                write!(
+                    f,
+                    "{}, {}, min {}",
+                    self.item,
+                    readable(amount),
+                    readable(min)
+                )
+            }
+            (Some(amount), None) => write!(f, "{}, {}", self.item, readable(amount)),
+            _ => write!(f, "{}", self.item),
+        }
+    }
+}
+
+/// Parse the amount on hand, which unlike the amount of an ingredient in a recipe may be zero
+fn parse_amount(input: &str) -> Result<Quantity, String> {
+    let mut parts = input.splitn(2, char::is_whitespace);
+    match (parts.next(), parts.next()) {
+        (Some("0"), unit) => {
+            let one: Quantity = Quantity::parse(&format!("1 {}", unit.unwrap_or("")))?;
+            Ok(one.checked_sub(&one).unwrap())
+        }
+        _ => Quantity::parse(input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pantry::Pantry;
+    use crate::qty::{Quantity, Volume, Weight};
+    use crate::recipe::Ingredient;
+
+    #[test]
+    fn test_subtract_pantry() {
+        let pantry = Pantry::parse("# Pantry\n- salt\n- Milk, 1 l\n- flour, 2 kg\n").unwrap();
+        let ingredients: Vec<Ingredient> = vec![
+            Ingredient::parse("- eggs, 3").unwrap(),
+            Ingredient::parse("- flour, 6 dl").unwrap(),
+            Ingredient::parse("- milk, 15 dl").unwrap(),
+            Ingredient::parse("- salt, 1 tsp").unwrap(),
+        ];
+
+        let list = pantry.subtract(ingredients);
+        let to_buy: Vec<&str> = list.to_buy.iter().map(|i| i.item.as_str()).collect();
+        assert_eq!(vec!["eggs", "flour", "milk"], to_buy);
+        assert_eq!(
+            Quantity::Volume(Volume::Milliliter(500)),
+            list.to_buy[2].amount
+        );
+        assert_eq!(
+            vec![Ingredient::parse("- salt, 1 tsp").unwrap()],
+            list.staples
+        );
+        assert_eq!(
+            vec!["Cannot subtract 2 kg of flour in the pantry from 6 dl, so all of it is bought"],
+            list.warnings
+        );
+    }
+
+    #[test]
+    fn test_drop_covered_items() {
+        let pantry = Pantry::parse("- flour, 2 kg").unwrap();
+        let list = pantry.subtract(vec![Ingredient::parse("- flour, 500 g").unwrap()]);
+        assert!(list.to_buy.is_empty());
+        assert!(list.staples.is_empty());
+
+        let list = pantry.subtract(vec![Ingredient::parse("- flour, 3 kg").unwrap()]);
+        assert_eq!(Quantity::Weight(Weight::Gram(1_000)), list.to_buy[0].amount);
+    }
+
+    #[test]
+    fn test_parse_invalid_pantry() {
+        assert!(Pantry::parse("- flour, lots of").is_err());
+        assert!(Pantry::parse("- flour, 2 kg, 1 kg").is_err());
+        assert!(Pantry::parse("- flour, 2 kg, min 1 kg, 500 g").is_err());
+        assert!(Pantry::parse("- milk, 2 l, min 1 kg").is_err());
+    }
+
+    #[test]
+    fn test_restock_below_minimum() {
+        let pantry = Pantry::parse("- milk, 5 dl, min 1 l\n- eggs, 0, min 6\n- salt").unwrap();
+        let list = pantry.subtract(vec![Ingredient::parse("- milk, 2 dl").unwrap()]);
+        assert_eq!(
+            Quantity::Volume(Volume::Milliliter(700)),
+            list.to_buy[0].amount
+        );
+        assert_eq!(Ingredient::parse("- eggs, 6").unwrap(), list.to_buy[1]);
+        assert_eq!(2, list.to_buy.len());
+    }
+
+    #[test]
+    fn test_consume() {
+        let mut pantry = Pantry::parse("- milk, 2 l, min 1 l\n- eggs, 6\n- salt").unwrap();
+        let ingredients: Vec<Ingredient> = vec![
+            Ingredient::parse("- milk, 15 dl").unwrap(),
+            Ingredient::parse("- eggs, 2").unwrap(),
+            Ingredient::parse("- salt, 1 tsp").unwrap(),
+            Ingredient::parse("- flour, 3 dl").unwrap(),
+        ];
+        let below: Vec<String> = pantry
+            .consume(&ingredients)
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(vec!["milk, 5 dl, min 1 l"], below);
+        assert_eq!(
+            Some(Quantity::Pieces(4)),
+            pantry.get("eggs").unwrap().amount
+        );
+        assert_eq!(None, pantry.get("salt").unwrap().amount);
+    }
+}
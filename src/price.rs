@@ -0,0 +1,246 @@
+use crate::csv::split_fields;
+use crate::package::{Choice, Package, Packages};
+use crate::qty::Quantity;
+use crate::recipe::{Ingredient, Recipe};
+use std::path::Path;
+
+/// The price of an ingredient in a package of a certain size
+#[derive(Debug, Clone, PartialEq)]
+pub struct Price {
+    pub item: String,
+    pub size: Quantity,
+    /// Price in cents (or the smallest unit of any other currency)
+    pub price: u32,
+    pub currency: String,
+}
+
+/// Prices of ingredients, all in the same currency
+#[derive(Debug, Clone, Default)]
+pub struct PriceList {
+    prices: Vec<Price>,
+}
+
+/// Estimated cost of a recipe, or of a shopping list
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cost {
+    /// Cost in cents (or the smallest unit of any other currency)
+    pub amount: u32,
+    /// Ingredients which have no price, and which therefore are not part of the cost
+    pub unpriced: Vec<String>,
+}
+
+impl PriceList {
+    /// Parse a price list in CSV format, with the columns ingredient, package size, price and
+    /// currency, such as `canned tomatoes,400 g,12.90,SEK`. Columns may be separated by semicolons
+    /// instead of commas, in which case decimal commas are allowed, as in the nutrition table. The
+    /// first line may be a header, and empty lines and lines starting with `#` are allowed.
+    pub fn parse(content: &str) -> Result<PriceList, String> {
+        let mut prices: Vec<Price> = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line: &str = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<String> = split_fields(line);
+            if fields.len() != 4 {
+                return Err(format!(
+                    "Expected 4 columns on line {} of price list",
+                    index + 1
+                ));
+            }
+
+            let price: u32 = match parse_money(&fields[2].replace(',', ".")) {
+                Some(price) => price,
+                None if index == 0 => continue,
+                None => {
+                    return Err(format!(
+                        "Invalid price on line {}: {}",
+                        index + 1,
+                        fields[2]
+                    ))
+                }
+            };
+
+            let size: Quantity = Quantity::parse(&fields[1])
+                .map_err(|e| format!("Invalid size on line {}: {}", index + 1, e))?;
+
+            prices.push(Price {
+                item: fields[0].to_lowercase(),
+                size,
+                price,
+                currency: fields[3].to_string(),
+            });
+        }
+
+        if let Some(price) = prices.iter().find(|p| p.currency != prices[0].currency) {
+            return Err(format!(
+                "Price list has prices in both {} and {}, only one currency is supported",
+                prices[0].currency, price.currency
+            ));
+        }
+
+        Ok(PriceList { prices })
+    }
+
+    pub fn from_file(path: &Path) -> Result<PriceList, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => PriceList::parse(&content),
+            Err(e) => Err(format!("Unable to read price list from {:?}: {}", path, e)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    pub fn currency(&self) -> &str {
+        self.prices
+            .first()
+            .map(|p| p.currency.as_str())
+            .unwrap_or("")
+    }
+
+    /// The price list as package sizes, with their prices
+    pub fn packages(&self) -> Packages {
+        let mut packages = Packages::default();
+        for price in &self.prices {
+            let package = Package {
+                size: price.size.clone(),
+                name: None,
+                price: Some(price.price),
+            };
+            packages.insert(&price.item, package);
+        }
+        packages
+    }
+
+    /// Estimated cost of the ingredient, in proportion to the price of the package with the lowest
+    /// price per unit, rather than the price of whole packages
+    pub fn ingredient_cost(&self, ingredient: &Ingredient) -> Option<u32> {
+        let need: u64 = ingredient.amount.base_amount() as u64;
+        self.prices
+            .iter()
+            .filter(|p: &&Price| p.item == ingredient.item)
+            .filter(|p: &&Price| p.size.is_same_kind(&ingredient.amount))
+            .filter(|p: &&Price| !p.size.is_zero())
+            .map(|p: &Price| {
+                let size: u64 = p.size.base_amount() as u64;
+                ((need * p.price as u64 + size / 2) / size) as u32
+            })
+            .min()
+    }
+
+    /// Estimated cost of the recipe, where every ingredient costs its share of a package
+    pub fn recipe_cost(&self, recipe: &Recipe) -> Cost {
+        let mut cost = Cost::default();
+        for ingredient in &recipe.ingredients {
+            match self.ingredient_cost(ingredient) {
+                Some(amount) => cost.amount += amount,
+                None => cost.unpriced.push(ingredient.item.clone()),
+            }
+        }
+        cost
+    }
+
+    /// Estimated cost of buying the (merged) ingredients, rounded up to whole packages
+    pub fn shopping_cost(&self, ingredients: &[Ingredient]) -> Cost {
+        let packages: Packages = self.packages();
+        let mut cost = Cost::default();
+        for ingredient in ingredients {
+            match packages.round(ingredient, Choice::Cheapest) {
+                Some(purchase) => {
+                    cost.amount += purchase
+                        .packages
+                        .iter()
+                        .map(|(n, p)| n * p.price.unwrap_or(0))
+                        .sum::<u32>()
+                }
+                None => cost.unpriced.push(ingredient.item.clone()),
+            }
+        }
+        cost
+    }
+
+    /// Format an amount in cents with the currency of the price list, such as `12.90 SEK`
+    pub fn format(&self, amount: u32) -> String {
+        format!("{}.{:02} {}", amount / 100, amount % 100, self.currency())
+            .trim_end()
+            .to_string()
+    }
+}
+
+/// Parse an amount of money such as `12.90` or `500` into cents
+pub fn parse_money(input: &str) -> Option<u32> {
+    let amount: f64 = input.trim().parse().ok()?;
+    if amount >= 0.0 && amount.is_finite() {
+        Some((amount * 100.0).round() as u32)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::price::{parse_money, PriceList};
+    use crate::qty::Quantity;
+    use crate::recipe::{Ingredient, Recipe};
+
+    const PRICES: &str = "ingredient,size,price,currency\n\
+                          canned tomatoes,400 g,12.90,SEK\n\
+                          cream,25 cl,9.50,SEK\n\
+                          cream,5 dl,15.00,SEK\n";
+
+    #[test]
+    fn test_parse_price_list() {
+        let prices = PriceList::parse(PRICES).unwrap();
+        assert_eq!("SEK", prices.currency());
+        assert_eq!("27.80 SEK", prices.format(2780));
+        assert!(PriceList::parse("cream,5 dl,15.00,SEK\nmilk,1 l,12.00,EUR").is_err());
+        assert!(PriceList::parse("cream,5 dl,15.00,SEK\nmilk,1 l,lots,SEK").is_err());
+        assert_eq!(Some(50_000), parse_money("500"));
+    }
+
+    #[test]
+    fn test_parse_quotes_and_semicolons() {
+        let prices = PriceList::parse("\"tomatoes, canned\",400 g,\"12,90\",SEK").unwrap();
+        let size: Quantity = Quantity::parse("400 g").unwrap();
+        let tomatoes = Ingredient::new("tomatoes, canned".to_string(), size);
+        assert_eq!(Some(1290), prices.ingredient_cost(&tomatoes));
+
+        let prices = PriceList::parse("ingredient;size;price;currency\ncream;5 dl;15,50;SEK");
+        assert_eq!("15.50 SEK", prices.unwrap().format(1550));
+    }
+
+    #[test]
+    fn test_only_first_line_is_header() {
+        assert!(PriceList::parse("cream,5 dl,cheap,SEK\nmilk,1 l,12.00,SEK").is_ok());
+        let error = PriceList::parse("# Prices\ncream,5 dl,cheap,SEK\nmilk,1 l,12.00,SEK");
+        assert_eq!("Invalid price on line 2: cheap", error.unwrap_err());
+    }
+
+    #[test]
+    fn test_recipe_cost() {
+        let prices = PriceList::parse(PRICES).unwrap();
+        let ingredients: Vec<Ingredient> = vec![
+            Ingredient::parse("- canned tomatoes, 800 g").unwrap(),
+            Ingredient::parse("- cream, 1 dl").unwrap(),
+            Ingredient::parse("- basil").unwrap(),
+        ];
+        let cost = prices.recipe_cost(&Recipe::new("Pasta", ingredients, 4));
+        assert_eq!(2580 + 300, cost.amount);
+        assert_eq!(vec!["basil".to_string()], cost.unpriced);
+    }
+
+    #[test]
+    fn test_shopping_cost() {
+        let prices = PriceList::parse(PRICES).unwrap();
+        let ingredients: Vec<Ingredient> = vec![
+            Ingredient::parse("- canned tomatoes, 1 kg").unwrap(),
+            Ingredient::parse("- cream, 3 dl").unwrap(),
+        ];
+        let cost = prices.shopping_cost(&ingredients);
+        assert_eq!(3 * 1290 + 1500, cost.amount);
+        assert!(cost.unpriced.is_empty());
+    }
+}
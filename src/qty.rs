@@ -1,6 +1,46 @@
 use std::fmt;
+use std::str::FromStr;
+
+/// The system of units that amounts are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Units {
+    /// Grams, kilograms, milliliters, centiliters, deciliters and liters
+    #[default]
+    Metric,
+    /// Ounces, pounds, teaspoons, tablespoons, fluid ounces, cups and pints
+    Imperial,
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Units::Metric => f.write_str("metric"),
+            Units::Imperial => f.write_str("imperial"),
+        }
+    }
+}
+
+impl FromStr for Units {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "metric" => Ok(Units::Metric),
+            "imperial" => Ok(Units::Imperial),
+            _ => Err(format!("Invalid units: {}", s)),
+        }
+    }
+}
 
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialize::Amount",
+        try_from = "crate::serialize::Amount"
+    )
+)]
 pub enum Quantity {
     Pieces(u32),
     Weight(Weight),
@@ -8,10 +48,6 @@ pub enum Quantity {
     Custom(u32, String),
 }
 
-trait StdUnit {
-    fn in_std_unit() -> u32;
-}
-
 impl std::ops::Add for Quantity {
     type Output = Quantity;
 
@@ -71,7 +107,7 @@ impl Quantity {
             return Ok(Quantity::Pieces(1));
         }
 
-        let parts: Vec<&str> = input.trim().split_whitespace().collect();
+        let parts: Vec<&str> = input.split_whitespace().collect();
         let num_input: &str = parts.first().unwrap();
         let number: u32 = match num_input.parse() {
             Ok(0) => return Err(String::from("Invalid amount: 0")),
@@ -107,6 +143,85 @@ impl Quantity {
         };
         Ok(parsed_quantity)
     }
+
+    /// Subtract `other` from this quantity, converting between units when both quantities are
+    /// weights or volumes. The result is never less than zero. Returns `None` if the quantities
+    /// are of different kinds, and therefore cannot be subtracted.
+    pub fn checked_sub(&self, other: &Quantity) -> Option<Quantity> {
+        let quantity: Quantity = match (self, other) {
+            (Quantity::Pieces(n0), Quantity::Pieces(n1)) => {
+                Quantity::Pieces(n0.saturating_sub(*n1))
+            }
+            (Quantity::Custom(n0, type0), Quantity::Custom(n1, type1)) if type0 == type1 => {
+                Quantity::Custom(n0.saturating_sub(*n1), type0.clone())
+            }
+            (Quantity::Volume(v0), Quantity::Volume(v1)) => {
+                let diff: u32 = v0.as_milliliters().saturating_sub(v1.as_milliliters());
+                Quantity::Volume(Volume::Milliliter(diff))
+            }
+            (Quantity::Weight(w0), Quantity::Weight(w1)) => {
+                let diff: u32 = w0.as_grams().saturating_sub(w1.as_grams());
+                Quantity::Weight(Weight::Gram(diff))
+            }
+            _ => return None,
+        };
+        Some(quantity)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount() == 0
+    }
+
+    /// The amount in grams for weights and in milliliters for volumes, so that quantities of the
+    /// same kind can be compared regardless of their unit
+    pub fn base_amount(&self) -> u32 {
+        match self {
+            Quantity::Weight(w) => w.as_grams(),
+            Quantity::Volume(v) => v.as_milliliters(),
+            Quantity::Pieces(n) | Quantity::Custom(n, _) => *n,
+        }
+    }
+
+    /// The number and the unit of the quantity as written, where the unit is empty for pieces
+    pub fn number_and_unit(&self) -> (u32, &str) {
+        match self {
+            Quantity::Weight(w) => (w.amount(), w.unit()),
+            Quantity::Volume(v) => (v.amount(), v.unit()),
+            Quantity::Pieces(n) => (*n, ""),
+            Quantity::Custom(n, t) => (*n, t),
+        }
+    }
+
+    /// The unit of [Quantity::base_amount], which is "g" for weights, "ml" for volumes, "pieces"
+    /// for pieces and the unit itself for custom units
+    pub fn base_unit(&self) -> &str {
+        match self {
+            Quantity::Weight(_) => "g",
+            Quantity::Volume(_) => "ml",
+            Quantity::Pieces(_) => "pieces",
+            Quantity::Custom(_, unit) => unit,
+        }
+    }
+
+    /// A quantity of the same kind as this one, with `amount` given in the base unit, see
+    /// [Quantity::base_amount]
+    pub fn with_base_amount(&self, amount: u32) -> Quantity {
+        match self {
+            Quantity::Weight(_) => Quantity::Weight(Weight::Gram(amount)),
+            Quantity::Volume(_) => Quantity::Volume(Volume::Milliliter(amount)),
+            Quantity::Pieces(_) => Quantity::Pieces(amount),
+            Quantity::Custom(_, unit) => Quantity::Custom(amount, unit.clone()),
+        }
+    }
+
+    /// Check if this quantity and `other` are of the same kind, so that one can be converted to
+    /// the unit of the other
+    pub fn is_same_kind(&self, other: &Quantity) -> bool {
+        match (self, other) {
+            (Quantity::Custom(_, type0), Quantity::Custom(_, type1)) => type0 == type1,
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
 }
 
 trait Quantifiable {
@@ -116,12 +231,7 @@ trait Quantifiable {
 
 impl fmt::Display for Quantity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (number, unit): (u32, &str) = match self {
-            Quantity::Weight(w) => (w.amount(), w.unit()),
-            Quantity::Volume(v) => (v.amount(), v.unit()),
-            Quantity::Pieces(n) => (*n, ""),
-            Quantity::Custom(n, t) => (*n, t),
-        };
+        let (number, unit): (u32, &str) = self.number_and_unit();
         write!(f, "{} {}", number, unit)
     }
 }
@@ -147,6 +257,14 @@ impl Quantifiable for Quantity {
 }
 
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialize::Amount",
+        try_from = "crate::serialize::Amount"
+    )
+)]
 pub enum Weight {
     Kilogram(u32),
     Gram(u32),
@@ -155,7 +273,7 @@ pub enum Weight {
 }
 
 const POUND_TO_GRAMS: f32 = 453.592_38;
-const OUNCE_TO_GRAMS: f32 = 28.349_524;
+pub const OUNCE_TO_GRAMS: f32 = 28.349_524;
 
 impl Weight {
     pub fn as_grams(&self) -> u32 {
@@ -230,6 +348,14 @@ impl Quantifiable for Volume {
 }
 
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        into = "crate::serialize::Amount",
+        try_from = "crate::serialize::Amount"
+    )
+)]
 pub enum Volume {
     Liter(u32),
     Deciliter(u32),
@@ -244,7 +370,7 @@ pub enum Volume {
 }
 
 const PINT_TO_ML: u32 = 473;
-const FLUID_OUNCE_TO_ML: f32 = 29.6;
+pub const FLUID_OUNCE_TO_ML: f32 = 29.6;
 const CUP_TO_ML: u32 = 237;
 
 impl Volume {
@@ -528,6 +654,26 @@ mod tests {
         assert!(Quantity::parse("-1").is_err());
     }
 
+    #[test]
+    fn test_checked_sub() {
+        let milk = Quantity::parse("1 l").unwrap();
+        let rest = milk.checked_sub(&Quantity::parse("3 dl").unwrap()).unwrap();
+        assert_eq!(Quantity::Volume(Volume::Milliliter(700)), rest);
+
+        let flour = Quantity::parse("500 g").unwrap();
+        let rest = flour
+            .checked_sub(&Quantity::parse("1 kg").unwrap())
+            .unwrap();
+        assert!(rest.is_zero());
+
+        let eggs = Quantity::parse("6").unwrap();
+        assert_eq!(
+            Some(Quantity::Pieces(4)),
+            eggs.checked_sub(&Quantity::Pieces(2))
+        );
+        assert_eq!(None, eggs.checked_sub(&Quantity::parse("2 dl").unwrap()));
+    }
+
     #[test]
     fn test_mul_assign() {
         let mut pieces = Quantity::Pieces(5);
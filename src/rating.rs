@@ -0,0 +1,127 @@
+use crate::recipe::{list_items, Recipe};
+use std::path::{Path, PathBuf};
+
+/// Rating used for recipes which have not been rated
+pub const DEFAULT_RATING: u8 = 3;
+pub const MAX_RATING: u8 = 5;
+
+/// Ratings of recipes, kept separately from the recipes themselves. A rating given here takes
+/// precedence over any rating given in the front matter of a recipe.
+#[derive(Debug, Clone, Default)]
+pub struct Ratings {
+    ratings: Vec<Rating>,
+}
+
+#[derive(Debug, Clone)]
+struct Rating {
+    /// The lowercase title of the recipe
+    title: String,
+    /// The canonical path to the recipe, if the key is a path to an existing file
+    path: Option<PathBuf>,
+    rating: u8,
+}
+
+impl Ratings {
+    /// Parse ratings where each rating is listed on a line of its own, preceded by a dash, with
+    /// the title of (or path to) the recipe followed by its rating, separated by a comma. For
+    /// example `- Pizza, 5`.
+    pub fn parse(content: &str) -> Result<Ratings, String> {
+        let ratings: Vec<Rating> = list_items(content)
+            .iter()
+            .map(|line: &String| parse_rating(line))
+            .collect::<Result<Vec<Rating>, String>>()?;
+
+        Ok(Ratings { ratings })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Ratings, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ratings::parse(&content),
+            Err(e) => Err(format!("Unable to read ratings from {:?}: {}", path, e)),
+        }
+    }
+
+    /// The rating of the recipe, from 0 (never select this recipe, unless there are no other
+    /// options) to [MAX_RATING]
+    pub fn rating(&self, recipe: &Recipe) -> u8 {
+        let title: String = recipe.name().to_lowercase();
+        let path: Option<PathBuf> = recipe.path.as_ref().and_then(|p| p.canonicalize().ok());
+        self.ratings
+            .iter()
+            .find(|r: &&Rating| r.title == title || (r.path.is_some() && r.path == path))
+            .map(|r: &Rating| r.rating)
+            .or(recipe.rating)
+            .unwrap_or(DEFAULT_RATING)
+            .min(MAX_RATING)
+    }
+
+    /// The relative weight for how often a recipe should be selected
+    pub fn weight(&self, recipe: &Recipe) -> f64 {
+        self.rating(recipe) as f64
+    }
+}
+
+/// Parse a single rating, where the key is canonicalized up front when it is a path to a file, so
+/// that it is only done once rather than for every recipe that is rated
+fn parse_rating(line: &str) -> Result<Rating, String> {
+    let mut parts = line.rsplitn(2, ',');
+    match (parts.next(), parts.next()) {
+        (Some(rating), Some(key)) => match rating.trim().parse::<u8>() {
+            Ok(rating) if rating <= MAX_RATING => Ok(Rating {
+                title: key.trim().to_lowercase(),
+                path: PathBuf::from(key.trim()).canonicalize().ok(),
+                rating,
+            }),
+            _ => Err(format!("Invalid rating '{}' in '{}'", rating.trim(), line)),
+        },
+        _ => Err(format!("Missing rating in '{}'", line)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rating::{Ratings, DEFAULT_RATING};
+    use crate::recipe::Recipe;
+
+    #[test]
+    fn test_parse_ratings() {
+        let ratings = Ratings::parse("# Ratings\n- Pizza, 5\n- Fish, soup, 1\n").unwrap();
+        let pizza = Recipe::new("# Pizza", vec![], 4);
+        let soup = Recipe::new("# Fish, soup", vec![], 4);
+        let pancakes = Recipe::new("# Pancakes", vec![], 4);
+        assert_eq!(5, ratings.rating(&pizza));
+        assert_eq!(1, ratings.rating(&soup));
+        assert_eq!(DEFAULT_RATING, ratings.rating(&pancakes));
+    }
+
+    #[test]
+    fn test_rating_by_path() {
+        let dir = std::env::temp_dir().join(format!("food-rating-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("pizza.md");
+        std::fs::write(&file, "# Pizza").unwrap();
+        let ratings = Ratings::parse(&format!("- {}, 5", file.display())).unwrap();
+        let mut pizza = Recipe::new("# Pizza", vec![], 4);
+        let other = Recipe::new("# Other pizza", vec![], 4);
+        pizza.path = Some(dir.join(".").join("pizza.md"));
+        assert_eq!(5, ratings.rating(&pizza));
+        assert_eq!(DEFAULT_RATING, ratings.rating(&other));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_invalid_ratings() {
+        assert!(Ratings::parse("- Pizza").is_err());
+        assert!(Ratings::parse("- Pizza, 6").is_err());
+        assert!(Ratings::parse("- Pizza, great").is_err());
+    }
+
+    #[test]
+    fn test_ratings_take_precedence_over_front_matter() {
+        let mut pizza = Recipe::new("# Pizza", vec![], 4);
+        pizza.rating = Some(2);
+        assert_eq!(2, Ratings::default().rating(&pizza));
+        let ratings = Ratings::parse("- pizza, 4").unwrap();
+        assert_eq!(4, ratings.rating(&pizza));
+    }
+}
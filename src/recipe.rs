@@ -1,15 +1,36 @@
-use crate::qty::{Quantity, Volume, Weight};
+use crate::qty::{Quantity, Units, Volume, Weight, FLUID_OUNCE_TO_ML, OUNCE_TO_GRAMS};
 use itertools::Itertools;
+use lazy_static::lazy_static;
+use regex::Regex;
 use std::cmp::Ordering;
 use std::fmt;
-use std::ops::{Add, MulAssign};
+use std::io::Read;
+use std::ops::MulAssign;
 use std::path::PathBuf;
 
+lazy_static! {
+    pub static ref ITEM_PATTERN: Regex = Regex::new(r"^\s*-\s+").unwrap();
+    pub static ref SERVINGS_PATTERN: Regex =
+        Regex::new(r"^\s*(servings|portioner):\s*\d+\s*$").unwrap();
+    pub static ref STEP_PATTERN: Regex = Regex::new(r"^\s*\d+\.\s+").unwrap();
+}
+
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Recipe {
     pub title: String,
     pub ingredients: Vec<Ingredient>,
     pub servings: Option<u8>,
+    pub rating: Option<u8>,
+    /// Number of numbered instructions in the recipe
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub steps: usize,
+    /// Total time in minutes needed to cook the recipe, if known
+    pub time: Option<u32>,
+    /// Tags of the recipe, such as `vegetarian` or `quick`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tags: Vec<String>,
+    pub path: Option<PathBuf>,
 }
 
 impl Recipe {
@@ -18,6 +39,11 @@ impl Recipe {
             title: title.to_string(),
             ingredients,
             servings: Some(servings),
+            rating: None,
+            steps: 0,
+            time: None,
+            tags: Vec::new(),
+            path: None,
         }
     }
 
@@ -25,6 +51,23 @@ impl Recipe {
         self.ingredients.len()
     }
 
+    /// The title of the recipe, without any leading markdown heading markers
+    pub fn name(&self) -> &str {
+        self.title.trim_start_matches('#').trim()
+    }
+
+    /// Check if this recipe is referred to by `key`, which may either be a path to the recipe
+    /// file or the title of the recipe (case insensitive)
+    pub fn is_referred_to_by(&self, key: &str) -> bool {
+        if self.name().to_lowercase() == key.trim().to_lowercase() {
+            return true;
+        }
+        match (&self.path, PathBuf::from(key).canonicalize()) {
+            (Some(path), Ok(key)) => path.canonicalize().map(|p| p == key).unwrap_or(false),
+            _ => false,
+        }
+    }
+
     pub fn apply_serving_size(&mut self, size: u8) {
         let current: u8 = match self.servings {
             Some(n) if n == size => return,
@@ -35,46 +78,163 @@ impl Recipe {
         let ratio: f32 = (size as f32) / (current as f32);
         self.ingredients
             .iter_mut()
-            .for_each(|i: &mut Ingredient| i.mul_assign(ratio))
+            .for_each(|i: &mut Ingredient| i.mul_assign(ratio));
+        self.servings = Some(size);
+    }
+
+    /// Read and parse a recipe from `reader`, see [Recipe::parse]. Returns `Ok(None)` if the
+    /// content is not a recipe.
+    pub fn from_reader<R: Read>(mut reader: R) -> std::io::Result<Option<Recipe>> {
+        let mut content = String::new();
+        reader.read_to_string(&mut content)?;
+        Ok(Recipe::parse(&content))
     }
 
     pub fn from_file(path: PathBuf) -> Option<Recipe> {
-        let lines: Vec<String> = match std::fs::read_to_string(path) {
-            Ok(content) => content.lines().map(str::to_owned).collect(),
+        let content: String = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
             Err(_) => return None,
         };
 
-        let title: String = lines
-            .first()
-            .clone()
-            .expect("Expected a first line")
-            .to_string();
+        let mut recipe: Recipe = Recipe::parse(&content)?;
+        recipe.path = Some(path);
+        Some(recipe)
+    }
+
+    /// The content of the file that the recipe was read from, if any
+    pub fn text(&self) -> Option<String> {
+        let path: &PathBuf = self.path.as_ref()?;
+        match std::fs::read_to_string(path) {
+            Ok(text) => Some(text),
+            Err(e) => {
+                log::warn!("Unable to read recipe from {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Parse a recipe from its markdown `content`. The recipe may optionally start with front
+    /// matter, delimited by `---` lines, with `key: value` pairs such as `rating: 4`. The first
+    /// line after any front matter is the title of the recipe.
+    pub fn parse(content: &str) -> Option<Recipe> {
+        let lines: Vec<&str> = content.lines().collect();
+        let (front_matter, lines): (&[&str], &[&str]) = split_front_matter(&lines);
+
+        let title: String = lines.first()?.to_string();
+
+        let rating: Option<u8> = front_matter_value(front_matter, "rating")
+            .and_then(|rating: &str| rating.parse::<u8>().ok());
+
+        let time: Option<u32> = front_matter_value(front_matter, "time").and_then(parse_minutes);
 
-        let mut servings: Option<u8> = None;
+        let tags: Vec<String> = match front_matter_value(front_matter, "tags") {
+            Some(tags) => tags
+                .split(',')
+                .map(|tag: &str| tag.trim().to_string())
+                .filter(|tag: &String| !tag.is_empty())
+                .collect(),
+            None => Vec::new(),
+        };
 
-        let ingredients: Vec<Ingredient> = lines
+        let servings: Option<u8> = front_matter
             .iter()
-            .inspect(|line| {
-                if crate::SERVINGS_PATTERN.is_match(&line) {
-                    let parts = line.split(':').collect::<Vec<&str>>();
-                    let last = parts.last().unwrap();
-                    let number: u8 = last.trim().parse().unwrap();
-                    servings = Some(number)
+            .chain(lines.iter())
+            .rev()
+            .find(|line| SERVINGS_PATTERN.is_match(line))
+            .and_then(|line| {
+                let parts = line.split(':').collect::<Vec<&str>>();
+                let last = parts.last().unwrap();
+                last.trim().parse::<u8>().ok()
+            });
+
+        let name: &str = title.trim_start_matches('#').trim();
+        let mut component: Option<String> = None;
+        let mut ingredients: Vec<Ingredient> = Vec::new();
+        for line in &lines[1..] {
+            if line.trim_start().starts_with('#') {
+                component = Some(line.trim_start().trim_start_matches('#').trim().to_string());
+            } else if ITEM_PATTERN.is_match(line) {
+                if let Ok(mut ingredient) = Ingredient::parse(line) {
+                    ingredient.sources = vec![Source {
+                        recipe: name.to_string(),
+                        component: component.clone(),
+                        amount: ingredient.amount.clone(),
+                    }];
+                    ingredients.push(ingredient);
                 }
-            })
-            .filter(|line| crate::ITEM_PATTERN.is_match(line))
-            .map(|line| Ingredient::parse(&line))
-            .filter_map(Result::ok)
-            .collect();
+            }
+        }
+
+        let steps: usize = lines
+            .iter()
+            .filter(|line| STEP_PATTERN.is_match(line))
+            .count();
 
         Some(Recipe {
             title,
             ingredients,
             servings,
+            rating,
+            steps,
+            time,
+            tags,
+            path: None,
         })
     }
 }
 
+const FRONT_MATTER_DELIMITER: &str = "---";
+
+/// Split `lines` into the lines of the front matter (excluding delimiters) and the remaining
+/// lines of the recipe
+fn split_front_matter<'a, 'b>(lines: &'a [&'b str]) -> (&'a [&'b str], &'a [&'b str]) {
+    if lines.first().map(|l| l.trim()) != Some(FRONT_MATTER_DELIMITER) {
+        return (&[], lines);
+    }
+    match lines[1..]
+        .iter()
+        .position(|l| l.trim() == FRONT_MATTER_DELIMITER)
+    {
+        Some(end) => (&lines[1..=end], &lines[end + 2..]),
+        None => (&[], lines),
+    }
+}
+
+/// The `content` of a recipe without its front matter, if it has any
+pub fn strip_front_matter(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let (_, lines): (&[&str], &[&str]) = split_front_matter(&lines);
+    lines.join("\n")
+}
+
+fn front_matter_value<'a>(front_matter: &[&'a str], key: &str) -> Option<&'a str> {
+    front_matter.iter().find_map(|line: &&str| {
+        let mut parts = line.splitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(k), Some(v)) if k.trim().eq_ignore_ascii_case(key) => Some(v.trim()),
+            _ => None,
+        }
+    })
+}
+
+/// Parse a duration such as `45`, `45 min`, `1 h` or `1 h 30 min` into minutes
+fn parse_minutes(input: &str) -> Option<u32> {
+    let parts: Vec<&str> = input.split_whitespace().collect();
+    let mut minutes: u32 = 0;
+    let mut i: usize = 0;
+    while i < parts.len() {
+        let number: u32 = parts[i].parse().ok()?;
+        let unit: String = parts.get(i + 1).unwrap_or(&"min").to_lowercase();
+        minutes += match unit.as_str() {
+            "h" | "hour" | "hours" | "tim" | "timme" | "timmar" => number * 60,
+            "m" | "min" | "mins" | "minute" | "minutes" | "minut" | "minuter" => number,
+            _ => return None,
+        };
+        i += 2;
+    }
+    Some(minutes)
+}
+
 impl Ord for Recipe {
     fn cmp(&self, other: &Self) -> Ordering {
         self.size().cmp(&other.size())
@@ -98,9 +258,28 @@ impl std::fmt::Display for Recipe {
 }
 
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ingredient {
     pub item: String,
     pub amount: Quantity,
+    /// The recipes that the ingredient is needed for, and how much for each recipe. This is empty
+    /// for ingredients that are not from a recipe.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub sources: Vec<Source>,
+}
+
+/// A recipe, and the component of the recipe (such as a sauce) if any, that needs an amount of
+/// an ingredient
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Source {
+    pub recipe: String,
+    /// The heading in the recipe that the ingredient is listed under
+    pub component: Option<String>,
+    pub amount: Quantity,
 }
 
 impl std::fmt::Display for Ingredient {
@@ -117,6 +296,7 @@ impl Ingredient {
                 let ingredient = Ingredient {
                     item: Ingredient::extract_ingredient(&parts),
                     amount: Quantity::Pieces(1),
+                    sources: Vec::new(),
                 };
                 Ok(ingredient)
             }
@@ -124,6 +304,7 @@ impl Ingredient {
                 let ingredient = Ingredient {
                     item: Ingredient::extract_ingredient(&parts),
                     amount: Quantity::parse(parts[1])?,
+                    sources: Vec::new(),
                 };
                 Ok(ingredient)
             }
@@ -132,12 +313,16 @@ impl Ingredient {
     }
 
     pub fn new(item: String, amount: Quantity) -> Ingredient {
-        Ingredient { item, amount }
+        Ingredient {
+            item,
+            amount,
+            sources: Vec::new(),
+        }
     }
 
     fn extract_ingredient(parts: &[&str]) -> String {
         let item: String = (*parts.first().unwrap()).to_string();
-        crate::ITEM_PATTERN.replace_all(&item, "").to_lowercase()
+        ITEM_PATTERN.replace_all(&item, "").to_lowercase()
     }
 }
 
@@ -149,48 +334,126 @@ impl std::ops::Add for Ingredient {
             panic!("Cannot add items of different type")
         }
         let quantity = self.amount + other.amount;
-        Ingredient::new(self.item, quantity)
+        let sources: Vec<Source> = [self.sources, other.sources].concat();
+        Ingredient {
+            item: self.item,
+            amount: quantity,
+            sources,
+        }
     }
 }
 
 impl std::ops::MulAssign<f32> for Ingredient {
     fn mul_assign(&mut self, rhs: f32) {
-        self.amount *= rhs
+        self.amount *= rhs;
+        self.sources
+            .iter_mut()
+            .for_each(|s: &mut Source| s.amount *= rhs);
+    }
+}
+
+impl Source {
+    /// The source with its amount in a readable unit of the system of units, such as
+    /// `Pizza: Sauce 5 dl`
+    pub fn describe(&self, units: Units) -> String {
+        let amount =
+            convert_unit(&Ingredient::new(String::new(), self.amount.clone()), units).amount;
+        let amount: String = amount.to_string().trim().to_string();
+        match &self.component {
+            Some(component) => format!("{}: {} {}", self.recipe, component, amount),
+            None => format!("{} {}", self.recipe, amount),
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe(Units::Metric))
+    }
+}
+
+/// Get the text of every list item in `content`, i.e. all lines that are preceded by a dash
+pub fn list_items(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter(|line| ITEM_PATTERN.is_match(line))
+        .map(|line| ITEM_PATTERN.replace_all(line, "").trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Scale the markdown `content` of a recipe to `servings`, by changing the amount of every
+/// ingredient and the number of servings. Everything else in the recipe is kept as it is.
+pub fn scale_content(content: &str, servings: u8) -> Result<String, String> {
+    let recipe: Recipe = Recipe::parse(content).ok_or("The file is not a recipe")?;
+    let current: u8 = match recipe.servings {
+        Some(n) if n > 0 => n,
+        _ => return Err(format!("{} has no servings to scale from", recipe.name())),
+    };
+    let ratio: f32 = (servings as f32) / (current as f32);
+
+    let mut scaled: String = content
+        .lines()
+        .map(|line: &str| {
+            if SERVINGS_PATTERN.is_match(line) {
+                let key: &str = line.split(':').next().unwrap();
+                format!("{}: {}", key, servings)
+            } else if ITEM_PATTERN.is_match(line) {
+                scale_item(line, ratio)
+            } else {
+                line.to_string()
+            }
+        })
+        .join("\n");
+    if content.ends_with('\n') {
+        scaled.push('\n');
     }
+    Ok(scaled)
+}
+
+/// Scale the amount of the ingredient on the `line`, keeping the item as it is written. Lines
+/// without an amount, or that cannot be parsed, are left unchanged.
+fn scale_item(line: &str, ratio: f32) -> String {
+    let (item, mut ingredient): (&str, Ingredient) = match line.split_once(',') {
+        Some((item, _)) => match Ingredient::parse(line) {
+            Ok(ingredient) => (item, ingredient),
+            Err(_) => return line.to_string(),
+        },
+        None => return line.to_string(),
+    };
+    ingredient.mul_assign(ratio);
+    format!(
+        "{}, {}",
+        item,
+        divide_unit(&ingredient).amount.to_string().trim()
+    )
 }
 
 pub fn join_ingredients(mut recipes: Vec<Recipe>) -> Vec<Ingredient> {
     let ingredients: Vec<Ingredient> = recipes
         .iter_mut()
-        .map(|r| r.ingredients.clone())
-        .flatten()
+        .flat_map(|r| r.ingredients.clone())
         .collect();
 
     merge(ingredients)
 }
 
+/// Merge the ingredients of the same item by adding their amounts. Amounts that cannot be added,
+/// such as a weight and a volume of the same item, are kept as separate ingredients, each with
+/// the sources of its own amount.
 pub fn merge(mut ingredients: Vec<Ingredient>) -> Vec<Ingredient> {
     ingredients.sort_by(|i0, i1| i0.item.cmp(&i1.item));
-    ingredients
-        .iter()
-        .group_by(|i| i.item.clone())
-        .into_iter()
-        .map(|(_, v)| sum(v.collect::<Vec<&Ingredient>>()))
-        .collect()
-}
-
-fn sum(ingredients: Vec<&Ingredient>) -> Ingredient {
-    ingredients[1..]
-        .iter()
-        .fold(ingredients[0].clone(), |i0, i1| try_add(&i0, i1))
-}
-
-fn try_add(i0: &Ingredient, i1: &Ingredient) -> Ingredient {
-    if i0.item != i1.item {
-        (*i0).clone()
-    } else {
-        (*i0).clone().add((*i1).clone())
+    let mut merged: Vec<Ingredient> = Vec::with_capacity(ingredients.len());
+    for ingredient in ingredients {
+        let same: Option<usize> = merged.iter().rposition(|m: &Ingredient| {
+            m.item == ingredient.item && m.amount.is_same_kind(&ingredient.amount)
+        });
+        match same {
+            Some(index) => merged[index] = merged[index].clone() + ingredient,
+            None => merged.push(ingredient),
+        }
     }
+    merged
 }
 
 pub fn divide_unit(i: &Ingredient) -> Ingredient {
@@ -222,13 +485,56 @@ pub fn divide_unit(i: &Ingredient) -> Ingredient {
     Ingredient {
         item: i.item.clone(),
         amount: q,
+        sources: i.sources.clone(),
     }
 }
 
+/// Convert the amount of the ingredient to a readable unit of the system of units, as with
+/// [divide_unit] for metric units. Imperial amounts are rounded to the nearest whole unit, and
+/// small volumes are given in teaspoons or tablespoons.
+pub fn convert_unit(i: &Ingredient, units: Units) -> Ingredient {
+    let amount: Quantity = match (units, &i.amount) {
+        (Units::Metric, _) => return divide_unit(i),
+        (Units::Imperial, Quantity::Weight(w)) => {
+            let ounces: u32 = rounded(w.as_grams(), OUNCE_TO_GRAMS);
+            match ounces % 16 {
+                0 => Quantity::Weight(Weight::Pounds(ounces / 16)),
+                _ => Quantity::Weight(Weight::Ounces(ounces)),
+            }
+        }
+        (Units::Imperial, Quantity::Volume(v)) => {
+            let milliliters: u32 = v.as_milliliters();
+            let fluid_ounces: u32 = rounded(milliliters, FLUID_OUNCE_TO_ML);
+            let volume: Volume = if milliliters < 30 && milliliters % 15 == 0 {
+                Volume::Tablespoon(milliliters / 15)
+            } else if milliliters < 30 {
+                Volume::Teaspoon(rounded(milliliters, 5.0))
+            } else if fluid_ounces % 16 == 0 {
+                Volume::Pints(fluid_ounces / 16)
+            } else if fluid_ounces % 8 == 0 {
+                Volume::Cups(fluid_ounces / 8)
+            } else {
+                Volume::Ounces(fluid_ounces)
+            };
+            Quantity::Volume(volume)
+        }
+        (Units::Imperial, amount) => amount.clone(),
+    };
+    Ingredient {
+        amount,
+        ..i.clone()
+    }
+}
+
+/// The number of whole units of the given size that is nearest to `amount`, but at least one
+fn rounded(amount: u32, size: f32) -> u32 {
+    ((amount as f32 / size).round() as u32).max(1)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::qty::{Quantity, Volume};
-    use crate::recipe::{divide_unit, merge};
+    use crate::qty::{Quantity, Units, Volume, Weight};
+    use crate::recipe::{convert_unit, divide_unit, join_ingredients, merge, scale_content};
     use crate::recipe::{Ingredient, Recipe};
 
     #[test]
@@ -282,6 +588,28 @@ mod tests {
         assert_eq!(Quantity::Volume(Volume::Deciliter(15u32)), milk.amount)
     }
 
+    #[test]
+    fn test_convert_to_imperial_units() {
+        let imperial = |input: &str| -> String {
+            let ingredient: Ingredient = Ingredient::parse(input).unwrap();
+            convert_unit(&ingredient, Units::Imperial).to_string()
+        };
+        assert_eq!("flour, 7 oz", imperial("- flour, 200 g"));
+        assert_eq!("flour, 1 lbs", imperial("- flour, 450 g"));
+        assert_eq!("flour, 2 lbs", imperial("- flour, 900 g"));
+        assert_eq!("milk, 1 pints", imperial("- milk, 1 pint"));
+        assert_eq!("milk, 17 fl oz", imperial("- milk, 5 dl"));
+        assert_eq!("milk, 1 cups", imperial("- milk, 237 ml"));
+        assert_eq!("cream, 7 fl oz", imperial("- cream, 2 dl"));
+        assert_eq!("oil, 1 tablespoon", imperial("- oil, 1 tbsp"));
+        assert_eq!("salt, 1 teaspoon", imperial("- salt, 2 krm"));
+        assert_eq!("eggs, 3", imperial("- eggs, 3").trim_end());
+        assert_eq!(
+            "milk, 5 dl",
+            convert_unit(&Ingredient::parse("- milk, 500 ml").unwrap(), Units::Metric).to_string()
+        );
+    }
+
     #[test]
     fn test_change_servings_size() {
         let ingredients: Vec<Ingredient> = vec![
@@ -304,5 +632,87 @@ mod tests {
         };
 
         assert_eq!(10u32, eggs);
+        assert_eq!(Some(8u8), recipe.servings);
+    }
+
+    #[test]
+    fn test_parse_recipe_with_front_matter() {
+        let content = "---\nrating: 5\nservings: 2\ntags: breakfast, quick\n- not an ingredient\n\
+                       ---\n# Pancakes\n - milk, 5 dl\n - eggs, 2";
+        let recipe = Recipe::parse(content).unwrap();
+        assert_eq!("# Pancakes", recipe.title);
+        assert_eq!(Some(5u8), recipe.rating);
+        assert_eq!(vec!["breakfast", "quick"], recipe.tags);
+        assert_eq!(Some(2u8), recipe.servings);
+        assert_eq!(2, recipe.size());
+    }
+
+    #[test]
+    fn test_parse_recipe_steps_and_time() {
+        let content = "---\ntime: 1 h 15 min\n---\n# Pancakes\n - milk, 5 dl\n1. Mix\n2. Fry";
+        let recipe = Recipe::parse(content).unwrap();
+        assert_eq!(2, recipe.steps);
+        assert_eq!(Some(75), recipe.time);
+    }
+
+    #[test]
+    fn test_parse_recipe_without_front_matter() {
+        let recipe = Recipe::parse("# Pancakes\nservings: 4\n---\n - milk, 5 dl").unwrap();
+        assert_eq!("# Pancakes", recipe.title);
+        assert_eq!(None, recipe.rating);
+        assert_eq!(Some(4u8), recipe.servings);
+        assert_eq!(1, recipe.size());
+    }
+
+    #[test]
+    fn test_merge_keeps_sources() {
+        let pancakes = Recipe::parse("# Pancakes\n - milk, 1 l").unwrap();
+        let pizza = Recipe::parse("# Pizza\n## Sauce\n - milk, 5 dl\n - basil").unwrap();
+        let items: Vec<Ingredient> = join_ingredients(vec![pancakes, pizza]);
+        let milk: Ingredient = divide_unit(&items[1]);
+        let sources: Vec<String> = milk.sources.iter().map(|s| s.to_string()).collect();
+        assert_eq!(vec!["Pancakes 1 l", "Pizza: Sauce 5 dl"], sources);
+    }
+
+    #[test]
+    fn test_merge_different_kinds() {
+        let pancakes = Recipe::parse("# Pancakes\n - flour, 3 dl").unwrap();
+        let pizza = Recipe::parse("# Pizza\n - flour, 500 g").unwrap();
+        let bread = Recipe::parse("# Bread\n - flour, 2 dl").unwrap();
+        let items: Vec<Ingredient> = join_ingredients(vec![pancakes, pizza, bread]);
+        let amounts: Vec<(Quantity, Vec<&str>)> = items
+            .iter()
+            .map(|i| {
+                (
+                    i.amount.clone(),
+                    i.sources.iter().map(|s| s.recipe.as_str()).collect(),
+                )
+            })
+            .collect();
+        let expected: Vec<(Quantity, Vec<&str>)> = vec![
+            (
+                Quantity::Volume(Volume::Milliliter(500)),
+                vec!["Pancakes", "Bread"],
+            ),
+            (Quantity::Weight(Weight::Gram(500)), vec!["Pizza"]),
+        ];
+        assert_eq!(expected, amounts);
+    }
+
+    #[test]
+    fn test_referred_to_by_title() {
+        let recipe = Recipe::new("# Pizza", vec![], 4u8);
+        assert_eq!("Pizza", recipe.name());
+        assert!(recipe.is_referred_to_by("pizza"));
+        assert!(recipe.is_referred_to_by(" Pizza "));
+        assert!(!recipe.is_referred_to_by("Pizza sauce"));
+    }
+
+    #[test]
+    fn test_scale_content() {
+        let content = "# Pancakes\nservings: 4\n\n- Milk, 6 dl\n- eggs, 4\n- salt\n\n1. Whisk\n";
+        let expected = "# Pancakes\nservings: 2\n\n- Milk, 3 dl\n- eggs, 2\n- salt\n\n1. Whisk\n";
+        assert_eq!(Ok(expected.to_string()), scale_content(content, 2));
+        assert!(scale_content("# Pancakes\n- milk, 6 dl", 2).is_err());
     }
 }
@@ -0,0 +1,585 @@
+use crate::category::Categories;
+use crate::html::{escape, markdown_to_html, STYLE};
+use crate::ics;
+use crate::json::Json;
+use crate::pantry::ShoppingList;
+use crate::qty::{Quantity, Units};
+use crate::recipe::{convert_unit, strip_front_matter, Ingredient, Recipe, Source};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use itertools::Itertools;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Version of the schema of the JSON output, which is increased whenever a field is removed or
+/// changes meaning. Fields may be added without changing the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Name of the application, which is used to identify the events of the iCalendar output
+const NAME: &str = env!("CARGO_PKG_NAME");
+
+/// How the shopping list is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Plain text, meant to be read by humans
+    #[default]
+    Text,
+    /// A single JSON document, meant to be read by other programs
+    Json,
+    /// A markdown checklist, which can be checked off on a phone
+    Markdown,
+    /// CSV with one ingredient per line, meant for spreadsheets
+    Csv,
+    /// A self-contained HTML page, meant to be printed
+    Html,
+    /// An iCalendar file with one event per recipe, for each day of the week
+    Ics,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name: &str = match self {
+            Format::Text => "text",
+            Format::Json => "json",
+            Format::Markdown => "markdown",
+            Format::Csv => "csv",
+            Format::Html => "html",
+            Format::Ics => "ics",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "markdown" | "md" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            "html" => Ok(Format::Html),
+            "ics" | "ical" => Ok(Format::Ics),
+            _ => Err(format!("Invalid format: {}", s)),
+        }
+    }
+}
+
+/// The selected recipes and the shopping list for them
+pub struct Report<'a> {
+    pub recipes: &'a [Recipe],
+    pub list: &'a ShoppingList,
+    /// How much the recipes overlap in their ingredients, if they were selected for overlap
+    pub overlap: Option<u32>,
+    /// The system of units that amounts are shown in, except in the JSON output
+    pub units: Units,
+}
+
+impl<'a> Report<'a> {
+    pub fn new(recipes: &'a [Recipe], list: &'a ShoppingList) -> Report<'a> {
+        Report {
+            recipes,
+            list,
+            overlap: None,
+            units: Units::default(),
+        }
+    }
+
+    /// The report as JSON, see the README for a description of the schema. All amounts are given
+    /// in grams, milliliters or pieces, or in the unit of the recipe for other units.
+    pub fn to_json(&self, categories: &Categories) -> Json {
+        Json::object(self.members(categories, None))
+    }
+
+    /// The context that templates are rendered with, which has the same fields as the JSON
+    /// output, with the addition of a `quantity` in a readable unit (such as "1 l") for every
+    /// amount, and a list of `categories`, each with a `name` and the `items` of the shopping
+    /// list in that category
+    pub fn to_context(&self, categories: &Categories) -> Json {
+        let grouped: Vec<Json> = categories
+            .group(self.list.to_buy.clone())
+            .iter()
+            .map(|(category, ingredients)| {
+                let items: Vec<Json> = ingredients
+                    .iter()
+                    .map(|i: &Ingredient| ingredient_json(i, Some(category), Some(self.units)))
+                    .collect();
+                Json::object(vec![
+                    ("name", Json::from(capitalize(category).as_str())),
+                    ("items", Json::Array(items)),
+                ])
+            })
+            .collect();
+
+        let mut members: Vec<(&str, Json)> = self.members(categories, Some(self.units));
+        members.push(("categories", Json::Array(grouped)));
+        Json::object(members)
+    }
+
+    fn members(
+        &self,
+        categories: &Categories,
+        readable: Option<Units>,
+    ) -> Vec<(&'static str, Json)> {
+        let recipes: Vec<Json> = self
+            .recipes
+            .iter()
+            .map(|r: &Recipe| recipe_json(r, readable))
+            .collect();
+        let list = |ingredients: &[Ingredient]| -> Vec<Json> {
+            ingredients
+                .iter()
+                .map(|i: &Ingredient| {
+                    ingredient_json(i, Some(categories.category(&i.item)), readable)
+                })
+                .collect()
+        };
+
+        vec![
+            ("version", Json::from(SCHEMA_VERSION)),
+            ("recipes", Json::Array(recipes)),
+            ("shopping_list", Json::Array(list(&self.list.to_buy))),
+            ("staples", Json::Array(list(&self.list.staples))),
+            ("overlap", Json::from(self.overlap)),
+        ]
+    }
+
+    /// The report as a markdown checklist, with the recipes listed first, and the ingredients
+    /// grouped by category if `categories` is given
+    pub fn to_markdown(&self, categories: Option<&Categories>) -> String {
+        let mut lines: Vec<String> = vec!["# Shopping list".to_string(), String::new()];
+        lines.push("## Recipes".to_string());
+        self.recipes
+            .iter()
+            .for_each(|r: &Recipe| lines.push(format!("- {}", recipe_line(r))));
+
+        match categories {
+            Some(categories) => {
+                for (category, ingredients) in categories.group(self.list.to_buy.clone()) {
+                    lines.push(String::new());
+                    lines.push(format!("## {}", capitalize(&category)));
+                    ingredients.iter().for_each(|i: &Ingredient| {
+                        lines.push(format!("- [ ] {}", text(i, self.units)))
+                    });
+                }
+            }
+            None => {
+                lines.push(String::new());
+                lines.push("## Ingredients".to_string());
+                self.list.to_buy.iter().for_each(|i: &Ingredient| {
+                    lines.push(format!("- [ ] {}", text(i, self.units)))
+                });
+            }
+        }
+
+        if !self.list.staples.is_empty() {
+            lines.push(String::new());
+            lines.push("## Staples assumed in stock".to_string());
+            self.list
+                .staples
+                .iter()
+                .for_each(|i: &Ingredient| lines.push(format!("- {}", text(i, self.units))));
+        }
+
+        lines.join("\n")
+    }
+
+    /// The ingredients to buy as CSV, with the columns item, amount, unit, category and recipes,
+    /// where recipes are the names of the recipes that need the ingredient, separated by
+    /// semicolons
+    pub fn to_csv(&self, categories: &Categories) -> String {
+        let mut lines: Vec<String> = vec!["item,amount,unit,category,recipes".to_string()];
+        for ingredient in &self.list.to_buy {
+            let ingredient: Ingredient = convert_unit(ingredient, self.units);
+            let (amount, unit): (u32, &str) = ingredient.amount.number_and_unit();
+            let recipes: String = ingredient
+                .sources
+                .iter()
+                .map(|s| &s.recipe)
+                .unique()
+                .join("; ");
+            let fields: Vec<String> = vec![
+                csv_field(&ingredient.item),
+                amount.to_string(),
+                csv_field(unit),
+                csv_field(categories.category(&ingredient.item)),
+                csv_field(&recipes),
+            ];
+            lines.push(fields.join(","));
+        }
+        lines.join("\n")
+    }
+
+    /// The report as a self-contained HTML page, with the recipes linked to their files and the
+    /// ingredients to buy grouped by category, as a checklist. The `texts` of the recipes, which
+    /// are the markdown of each recipe, in the same order as the recipes, are added last without
+    /// their front matter. Pass no texts to leave them out.
+    pub fn to_html(&self, categories: &Categories, texts: &[String]) -> String {
+        let mut html: Vec<String> = vec![
+            "<!DOCTYPE html>".to_string(),
+            "<html>".to_string(),
+            "<head>".to_string(),
+            "<meta charset=\"utf-8\">".to_string(),
+            "<title>Shopping list</title>".to_string(),
+            format!("<style>\n{}\n</style>", STYLE),
+            "</head>".to_string(),
+            "<body>".to_string(),
+            "<h1>Meal plan</h1>".to_string(),
+            "<ul class=\"recipes\">".to_string(),
+        ];
+        for recipe in self.recipes {
+            let title: String = match &recipe.path {
+                Some(path) => {
+                    format!(
+                        "<a href=\"{}\">{}</a>",
+                        file_url(path),
+                        escape(recipe.name())
+                    )
+                }
+                None => escape(recipe.name()),
+            };
+            match recipe.servings {
+                Some(servings) => html.push(format!("<li>{}, {} servings</li>", title, servings)),
+                None => html.push(format!("<li>{}</li>", title)),
+            }
+        }
+        html.push("</ul>".to_string());
+
+        html.push("<h1>Shopping list</h1>".to_string());
+        for (category, ingredients) in categories.group(self.list.to_buy.clone()) {
+            html.push("<section class=\"category\">".to_string());
+            html.push(format!("<h2>{}</h2>", escape(&capitalize(&category))));
+            html.push("<ul class=\"checklist\">".to_string());
+            for ingredient in &ingredients {
+                let item: String = escape(&text(ingredient, self.units));
+                html.push(format!(
+                    "<li><label><input type=\"checkbox\"> {}</label></li>",
+                    item
+                ));
+            }
+            html.push("</ul>".to_string());
+            html.push("</section>".to_string());
+        }
+
+        if !self.list.staples.is_empty() {
+            html.push("<h2>Staples assumed in stock</h2>".to_string());
+            html.push("<ul>".to_string());
+            for ingredient in &self.list.staples {
+                html.push(format!(
+                    "<li>{}</li>",
+                    escape(&text(ingredient, self.units))
+                ));
+            }
+            html.push("</ul>".to_string());
+        }
+
+        for text in texts {
+            html.push("<article class=\"recipe\">".to_string());
+            html.push(markdown_to_html(&strip_front_matter(text)));
+            html.push("</article>".to_string());
+        }
+
+        html.push("</body>".to_string());
+        html.push("</html>".to_string());
+        html.join("\n")
+    }
+
+    /// The recipes as an iCalendar file, with one event for each recipe, on each day of the week
+    /// starting at `week_start`. The events last all day, unless a `dinner` time is given, in
+    /// which case they start at that time and last for an hour. `stamp` is the time that the
+    /// calendar is created, in UTC.
+    pub fn to_ics(
+        &self,
+        week_start: NaiveDate,
+        dinner: Option<NaiveTime>,
+        stamp: NaiveDateTime,
+    ) -> String {
+        let mut lines: Vec<String> = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            format!("PRODID:-//{}//Meal plan//EN", NAME),
+            "CALSCALE:GREGORIAN".to_string(),
+        ];
+
+        for (index, recipe) in self.recipes.iter().enumerate() {
+            let date: NaiveDate = week_start + Duration::days((index % 7) as i64);
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}-{}@{}", date.format("%Y%m%d"), index, NAME));
+            lines.push(format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")));
+            match dinner {
+                Some(time) => {
+                    let start: NaiveDateTime = date.and_time(time);
+                    let end: NaiveDateTime = start + Duration::hours(1);
+                    lines.push(format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")));
+                    lines.push(format!("DTEND:{}", end.format("%Y%m%dT%H%M%S")));
+                }
+                None => {
+                    let end: NaiveDate = date + Duration::days(1);
+                    lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+                    lines.push(format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")));
+                }
+            }
+            lines.push(format!("SUMMARY:{}", ics::escape(recipe.name())));
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                ics::escape(&description(recipe, self.units))
+            ));
+            lines.push("END:VEVENT".to_string());
+        }
+
+        lines.push("END:VCALENDAR".to_string());
+        lines.iter().map(|line: &String| ics::fold(line)).collect()
+    }
+}
+
+/// The servings, ingredients and file of the recipe
+fn description(recipe: &Recipe, units: Units) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    if let Some(servings) = recipe.servings {
+        lines.push(format!("{} servings", servings));
+        lines.push(String::new());
+    }
+    recipe
+        .ingredients
+        .iter()
+        .for_each(|i: &Ingredient| lines.push(text(i, units)));
+    if let Some(path) = &recipe.path {
+        lines.push(String::new());
+        lines.push(path.to_string_lossy().to_string());
+    }
+    lines.join("\n")
+}
+
+/// A link to a local file, which is made absolute so that the links work wherever the page is
+/// saved
+fn file_url(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let path: String = path
+        .to_string_lossy()
+        .replace('\\', "/")
+        .replace(' ', "%20");
+    let separator: &str = if path.starts_with('/') { "" } else { "/" };
+    escape(&format!("file://{}{}", separator, path))
+}
+
+fn recipe_line(recipe: &Recipe) -> String {
+    match recipe.servings {
+        Some(servings) => format!("{} ({} servings)", recipe.name(), servings),
+        None => recipe.name().to_string(),
+    }
+}
+
+/// The ingredient with its amount in a readable unit, such as "milk, 1 l"
+fn text(ingredient: &Ingredient, units: Units) -> String {
+    convert_unit(ingredient, units)
+        .to_string()
+        .trim_end()
+        .to_string()
+}
+
+pub fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Quote the field if it contains a comma, a quote or a line break
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn recipe_json(recipe: &Recipe, readable: Option<Units>) -> Json {
+    let path: Option<String> = recipe
+        .path
+        .as_ref()
+        .map(|p| p.to_string_lossy().to_string());
+    let ingredients: Vec<Json> = recipe
+        .ingredients
+        .iter()
+        .map(|i: &Ingredient| ingredient_json(i, None, readable))
+        .collect();
+
+    Json::object(vec![
+        ("title", Json::from(recipe.name())),
+        ("path", Json::from(path.as_deref())),
+        ("servings", Json::from(recipe.servings.map(u32::from))),
+        ("ingredients", Json::Array(ingredients)),
+    ])
+}
+
+fn ingredient_json(
+    ingredient: &Ingredient,
+    category: Option<&str>,
+    readable: Option<Units>,
+) -> Json {
+    let mut members: Vec<(&str, Json)> = vec![("item", Json::from(ingredient.item.as_str()))];
+    members.extend(amount_json(&ingredient.amount, readable));
+    if let Some(category) = category {
+        members.push(("category", Json::from(category)));
+        let sources: Vec<Json> = ingredient
+            .sources
+            .iter()
+            .map(|s: &Source| source_json(s, readable))
+            .collect();
+        members.push(("sources", Json::Array(sources)));
+    }
+    Json::object(members)
+}
+
+fn source_json(source: &Source, readable: Option<Units>) -> Json {
+    let mut members: Vec<(&str, Json)> = vec![
+        ("recipe", Json::from(source.recipe.as_str())),
+        ("component", Json::from(source.component.as_deref())),
+    ];
+    members.extend(amount_json(&source.amount, readable));
+    Json::object(members)
+}
+
+/// The amount and unit, and the quantity in a readable unit of the system of units `readable`,
+/// if it is set
+fn amount_json(amount: &Quantity, readable: Option<Units>) -> Vec<(&'static str, Json)> {
+    let mut members: Vec<(&str, Json)> = vec![
+        ("amount", Json::from(amount.base_amount())),
+        ("unit", Json::from(amount.base_unit())),
+    ];
+    if let Some(units) = readable {
+        let ingredient = Ingredient::new(String::new(), amount.clone());
+        let quantity: Quantity = convert_unit(&ingredient, units).amount;
+        members.push(("quantity", Json::from(quantity.to_string().trim())));
+    }
+    members
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::category::Categories;
+    use crate::json::Json;
+    use crate::pantry::Pantry;
+    use crate::recipe::{join_ingredients, Recipe};
+    use crate::report::{Format, Report};
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(Ok(Format::Json), "JSON".parse::<Format>());
+        assert!("yaml".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn test_to_markdown_and_csv() {
+        let content = "# Pizza\n## Sauce\n - canned tomatoes, 400 g\n - basil\n\
+                       ## Dough\n - salt, 1 tsp";
+        let recipe = Recipe::parse(content).unwrap();
+        let pantry = Pantry::parse("- salt").unwrap();
+        let list = pantry.subtract(join_ingredients(vec![recipe.clone()]));
+        let recipes = vec![recipe];
+        let report = Report::new(&recipes, &list);
+
+        let expected = "# Shopping list\n\n## Recipes\n- Pizza\n\n## Ingredients\n\
+                        - [ ] basil, 1\n- [ ] canned tomatoes, 400 g\n\n\
+                        ## Staples assumed in stock\n- salt, 5 ml";
+        assert_eq!(expected, report.to_markdown(None));
+        let grouped: String = report.to_markdown(Some(&Categories::default()));
+        assert!(grouped.contains("## Produce\n- [ ] basil, 1\n\n## Dry goods\n- [ ] canned"));
+
+        let expected = "item,amount,unit,category,recipes\n\
+                        basil,1,,produce,Pizza\n\
+                        canned tomatoes,400,g,dry goods,Pizza";
+        assert_eq!(expected, report.to_csv(&Categories::default()));
+    }
+
+    #[test]
+    fn test_to_html() {
+        let recipe = Recipe::parse("# Fish & chips\nservings: 2\n - cod, 400 g").unwrap();
+        let list = Pantry::default().subtract(join_ingredients(vec![recipe.clone()]));
+        let recipes = vec![recipe];
+        let report = Report::new(&recipes, &list);
+
+        let html: String = report.to_html(&Categories::default(), &[]);
+        assert!(html.starts_with("<!DOCTYPE html>\n<html>"));
+        assert!(html.contains("<li>Fish &amp; chips, 2 servings</li>"));
+        assert!(html.contains(
+            "<h2>Meat</h2>\n<ul class=\"checklist\">\n\
+                               <li><label><input type=\"checkbox\"> cod, 400 g</label></li>"
+        ));
+        assert!(!html.contains("<article"));
+
+        let texts = vec!["---\nrating: 5\ntags: quick\n---\n# Fish & chips\n\n1. Fry".to_string()];
+        let html: String = report.to_html(&Categories::default(), &texts);
+        assert!(html.contains("<article class=\"recipe\">\n<h2>Fish &amp; chips</h2>"));
+        assert!(!html.contains("rating"));
+    }
+
+    #[test]
+    fn test_to_context() {
+        let recipe = Recipe::parse("# Pancakes\n - milk, 10 dl\n - eggs, 3").unwrap();
+        let list = Pantry::default().subtract(join_ingredients(vec![recipe.clone()]));
+        let recipes = vec![recipe];
+        let context: String = Report::new(&recipes, &list)
+            .to_context(&Categories::default())
+            .to_string();
+        assert!(context.contains("\"unit\": \"ml\",\n      \"quantity\": \"1 l\",\n"));
+        assert!(context.contains("\"categories\": [\n    {\n      \"name\": \"Dairy\",\n"));
+    }
+
+    #[test]
+    fn test_to_ics() {
+        let recipes = vec![
+            Recipe::parse("# Pancakes\nservings: 4\n - milk, 6 dl\n - eggs, 3").unwrap(),
+            Recipe::parse("# Soup\n - mushrooms, 500 g").unwrap(),
+        ];
+        let list = Pantry::default().subtract(join_ingredients(recipes.clone()));
+        let report = Report::new(&recipes, &list);
+        let start = NaiveDate::from_ymd(2020, 9, 13);
+        let stamp: NaiveDateTime = start.and_hms(12, 0, 0);
+
+        let ics: String = report.to_ics(start, None, stamp);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(ics.contains(
+            "UID:20200913-0@food\r\nDTSTAMP:20200913T120000Z\r\n\
+                              DTSTART;VALUE=DATE:20200913\r\nDTEND;VALUE=DATE:20200914\r\n\
+                              SUMMARY:Pancakes\r\n\
+                              DESCRIPTION:4 servings\\n\\nmilk\\, 6 dl\\neggs\\, 3\r\n"
+        ));
+
+        let ics: String = report.to_ics(start, Some(NaiveTime::from_hms(18, 30, 0)), stamp);
+        assert!(ics.contains(
+            "UID:20200914-1@food\r\nDTSTAMP:20200913T120000Z\r\n\
+                              DTSTART:20200914T183000\r\nDTEND:20200914T193000\r\n"
+        ));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let content = "# Pancakes\nservings: 4\n - milk, 1 l\n - salt, 1 tsp";
+        let recipe = Recipe::parse(content).unwrap();
+        let pantry = Pantry::parse("- salt").unwrap();
+        let list = pantry.subtract(join_ingredients(vec![recipe.clone()]));
+        let recipes = vec![recipe];
+        let json: Json = Report::new(&recipes, &list).to_json(&Categories::default());
+        let output: String = json.to_string();
+
+        assert!(output.starts_with("{\n  \"version\": 1,\n"));
+        assert!(output.contains("\"title\": \"Pancakes\",\n      \"path\": null,"));
+        assert!(output.contains("\"amount\": 1000,\n      \"unit\": \"ml\",\n"));
+        assert!(output.contains("\"category\": \"dairy\""));
+        assert!(output.contains("\"recipe\": \"Pancakes\",\n          \"component\": null,"));
+        assert!(output.contains("\"staples\": [\n    {\n      \"item\": \"salt\""));
+        assert!(output.ends_with("\"overlap\": null\n}"));
+
+        let report = Report {
+            overlap: Some(3),
+            ..Report::new(&recipes, &list)
+        };
+        let output: String = report.to_json(&Categories::default()).to_string();
+        assert!(output.ends_with("\"overlap\": 3\n}"));
+    }
+}
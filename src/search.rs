@@ -0,0 +1,113 @@
+use crate::exclude::{contains_words, Exclusions};
+use crate::recipe::{Ingredient, Recipe};
+use itertools::Itertools;
+use std::fmt;
+
+/// A recipe that uses some of the ingredients that were searched for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match<'a> {
+    pub recipe: &'a Recipe,
+    /// The ingredients searched for that the recipe uses
+    pub used: Vec<String>,
+    /// The ingredients of the recipe that were not searched for, which are needed as well
+    pub missing: Vec<String>,
+}
+
+/// Find the recipes that use any of the ingredients `with`, and none of the ingredients (or
+/// groups of ingredients) in `without`. Ingredients are matched the same way as exclusions, so
+/// that "mushroom" matches "button mushrooms". The recipes that use the most of the ingredients
+/// come first, followed by those that need the fewest other ingredients. If `with` is empty, all
+/// recipes that are not excluded are found.
+pub fn search<'a>(recipes: &'a [Recipe], with: &[String], without: &Exclusions) -> Vec<Match<'a>> {
+    let with: Vec<String> = with.iter().map(|w| w.trim().to_lowercase()).collect();
+    let is_used = |item: &str| with.iter().any(|w: &String| contains_words(item, w));
+
+    recipes
+        .iter()
+        .filter(|r: &&Recipe| without.check(r).is_none())
+        .map(|recipe: &Recipe| {
+            let items: Vec<&str> = recipe
+                .ingredients
+                .iter()
+                .map(|i: &Ingredient| i.item.as_str())
+                .unique()
+                .collect();
+            let used: Vec<String> = with
+                .iter()
+                .filter(|w| items.iter().any(|item| contains_words(item, w)))
+                .cloned()
+                .collect();
+            let missing: Vec<String> = items
+                .iter()
+                .filter(|item| !is_used(item))
+                .map(|item| item.to_string())
+                .collect();
+            Match {
+                recipe,
+                used,
+                missing,
+            }
+        })
+        .filter(|m: &Match| with.is_empty() || !m.used.is_empty())
+        .sorted_by(|a: &Match, b: &Match| {
+            b.used
+                .len()
+                .cmp(&a.used.len())
+                .then(a.missing.len().cmp(&b.missing.len()))
+                .then_with(|| a.recipe.name().cmp(b.recipe.name()))
+        })
+        .collect()
+}
+
+impl fmt::Display for Match<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.recipe.name())?;
+        if !self.used.is_empty() {
+            write!(f, ", uses {}", self.used.join(", "))?;
+        }
+        if self.missing.is_empty() {
+            write!(f, ", nothing missing")
+        } else {
+            write!(f, ", missing {}", self.missing.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exclude::Exclusions;
+    use crate::recipe::Recipe;
+    use crate::search::{search, Match};
+
+    #[test]
+    fn test_search() {
+        let recipes: Vec<Recipe> = vec![
+            "# Lemon chicken\n- chicken thighs, 500 g\n- lemons, 2\n- garlic",
+            "# Chicken curry\n- chicken, 500 g\n- cream, 2 dl\n- lemon, 1\n- rice, 3 dl",
+            "# Chicken soup\n- chicken, 300 g\n- carrots, 3\n- onion\n- garlic",
+            "# Pancakes\n- milk, 6 dl\n- eggs, 3",
+        ]
+        .into_iter()
+        .map(|content| Recipe::parse(content).unwrap())
+        .collect();
+
+        let with: Vec<String> = vec!["Chicken".to_string(), "lemon".to_string()];
+        let matches: Vec<Match> = search(&recipes, &with, &Exclusions::new(&[]));
+        let names: Vec<&str> = matches.iter().map(|m: &Match| m.recipe.name()).collect();
+        assert_eq!(
+            vec!["Lemon chicken", "Chicken curry", "Chicken soup"],
+            names
+        );
+        assert_eq!(vec!["garlic"], matches[0].missing);
+        assert_eq!(
+            "Lemon chicken, uses chicken, lemon, missing garlic",
+            matches[0].to_string()
+        );
+
+        let matches: Vec<Match> = search(&recipes, &with, &Exclusions::new(&["dairy".into()]));
+        assert_eq!(2, matches.len());
+        assert!(matches
+            .iter()
+            .all(|m: &Match| m.recipe.name() != "Chicken curry"));
+    }
+}
@@ -0,0 +1,507 @@
+use crate::complexity::{complexity, prefer, Preference};
+use crate::exclude::Exclusions;
+use crate::nutrition::NutritionTable;
+use crate::overlap::{select_overlapping, shared_items, Perishables};
+use crate::price::PriceList;
+use crate::rating::Ratings;
+use crate::recipe::{join_ingredients, Recipe};
+use crate::target::{select_for_targets, Target};
+use rand::prelude::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// How the remaining recipes are chosen among the candidates, once pinned recipes are selected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Take the candidates in the order they are given, which is random
+    #[default]
+    Random,
+    /// Prefer candidates that share ingredients with each other, to minimize waste
+    Overlap,
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "random" => Ok(Strategy::Random),
+            "overlap" => Ok(Strategy::Overlap),
+            _ => Err(format!("Invalid strategy: {}", s)),
+        }
+    }
+}
+
+/// Criteria for which, and how many, recipes to select
+#[derive(Debug, Clone, Default)]
+pub struct Criteria {
+    /// Maximum number of recipes to select, unless more recipes than this are pinned
+    pub limit: usize,
+    /// Seed for any random choices made while selecting recipes
+    pub seed: u64,
+    /// Paths to, or titles of, recipes that must be selected
+    pub pins: Vec<String>,
+    /// Preferred complexity of the selected recipes, if any
+    pub preference: Option<Preference>,
+    /// Never select recipes with a higher complexity than this, unless pinned
+    pub max_complexity: Option<u32>,
+    /// How the slots that are not taken by pinned recipes are filled
+    pub strategy: Strategy,
+    /// Serving size that all recipes are scaled to, if any
+    pub serving_size: Option<u8>,
+    /// Maximum estimated cost, in cents, of all ingredients of the selected recipes
+    pub budget: Option<u32>,
+    /// Targets for the nutrition per serving of the selected recipes
+    pub targets: Vec<Target>,
+    pub exclusions: Exclusions,
+    pub ratings: Ratings,
+    pub perishables: Perishables,
+    pub prices: PriceList,
+    pub nutrition: NutritionTable,
+}
+
+/// Select recipes from `files`, which are expected to already be in the (seeded) order in which
+/// they should be considered, according to the `criteria`. Recipes referred to by any of the pins
+/// are always part of the selection, and only the remaining slots are filled with recipes from
+/// `files`. Recipes which contain any ingredient matching the exclusions, or which are too complex,
+/// are never selected unless pinned. Recipes with a higher rating are more likely to be selected.
+/// If there are nutrition targets, recipes are swapped to meet the targets as far as possible.
+/// If there is a budget, the most expensive recipes are swapped for cheaper recipes until the
+/// selection fits within the budget, as far as possible.
+pub fn select_recipes(files: Vec<PathBuf>, criteria: &Criteria) -> Result<Vec<Recipe>, String> {
+    let Criteria {
+        limit,
+        seed,
+        pins,
+        preference,
+        max_complexity,
+        strategy,
+        serving_size,
+        budget,
+        targets,
+        exclusions,
+        ratings,
+        perishables,
+        prices,
+        nutrition,
+    } = criteria;
+
+    if !targets.is_empty() && nutrition.is_empty() {
+        return Err("Nutrition targets require a nutrition table, see --nutrition".to_string());
+    }
+
+    // Every recipe is read up front, since the weighted order depends on the rating of each one
+    let mut candidates: Vec<Recipe> = files.into_iter().filter_map(Recipe::from_file).collect();
+    let mut pinned: Vec<Recipe> = find_pinned(&candidates, pins)?;
+    if let Some(servings) = serving_size {
+        candidates
+            .iter_mut()
+            .chain(pinned.iter_mut())
+            .for_each(|r: &mut Recipe| r.apply_serving_size(*servings));
+    }
+
+    if pinned.len() > *limit {
+        log::warn!(
+            "{} recipes are pinned, which exceeds the limit of {} recipes",
+            pinned.len(),
+            limit
+        );
+    }
+    for recipe in &pinned {
+        if let Some(exclusion) = exclusions.check(recipe) {
+            log::warn!(
+                "Pinned recipe '{}' contains {}, which is excluded by '{}'",
+                recipe.name(),
+                exclusion.item,
+                exclusion.term
+            );
+        }
+    }
+
+    if let Some(max) = max_complexity {
+        for recipe in pinned.iter().filter(|r: &&Recipe| complexity(r) > *max) {
+            log::warn!(
+                "Pinned recipe '{}' has complexity {}, which exceeds the max complexity {}",
+                recipe.name(),
+                complexity(recipe),
+                max
+            );
+        }
+    }
+
+    let remaining: usize = limit.saturating_sub(pinned.len());
+    let candidates: Vec<Recipe> = candidates
+        .into_iter()
+        .filter(|r: &Recipe| !pinned.contains(r))
+        .collect();
+
+    let candidates: Vec<Recipe> = exclude(candidates, exclusions);
+    let candidates: Vec<Recipe> = match max_complexity {
+        Some(max) => exclude_complex(candidates, *max),
+        None => candidates,
+    };
+    let candidates: Vec<Recipe> = weighted_order(candidates, ratings, *seed);
+    let candidates: Vec<Recipe> = match preference {
+        Some(preference) => prefer(candidates, *preference),
+        None => candidates,
+    };
+
+    let (selected, rest): (Vec<Recipe>, Vec<Recipe>) = match strategy {
+        Strategy::Random => {
+            let mut selected: Vec<Recipe> = candidates;
+            let rest: Vec<Recipe> = selected.split_off(remaining.min(selected.len()));
+            (selected, rest)
+        }
+        Strategy::Overlap => {
+            let selected = select_overlapping(&pinned, candidates.clone(), remaining, perishables);
+            report_overlap(&[&pinned[..], &selected[..]].concat());
+            let rest: Vec<Recipe> = candidates
+                .into_iter()
+                .filter(|r: &Recipe| !selected.contains(r))
+                .collect();
+            (selected, rest)
+        }
+    };
+
+    let (selected, rest): (Vec<Recipe>, Vec<Recipe>) = match targets.is_empty() {
+        true => (selected, rest),
+        false => select_for_targets(&pinned, selected, rest, nutrition, targets),
+    };
+
+    let selected: Vec<Recipe> = match budget {
+        Some(budget) => fit_budget(&pinned, selected, rest, prices, *budget),
+        None => selected,
+    };
+
+    Ok([pinned, selected].concat())
+}
+
+/// Swap the most expensive of the `selected` recipes for the first of the `rest` of the candidates
+/// that is cheaper, until the estimated cost of all ingredients fits within the budget or there
+/// are no cheaper candidates left. Pinned recipes are never swapped.
+fn fit_budget(
+    pinned: &[Recipe],
+    mut selected: Vec<Recipe>,
+    mut rest: Vec<Recipe>,
+    prices: &PriceList,
+    budget: u32,
+) -> Vec<Recipe> {
+    if prices.is_empty() {
+        log::warn!("A budget is set, but there is no price list to estimate the cost with");
+        return selected;
+    }
+
+    loop {
+        let plan: Vec<Recipe> = [pinned, &selected[..]].concat();
+        let cost: u32 = prices.shopping_cost(&join_ingredients(plan)).amount;
+        if cost <= budget {
+            log::info!(
+                "Estimated cost {} is within the budget",
+                prices.format(cost)
+            );
+            return selected;
+        }
+
+        let most_expensive: Option<(usize, u32)> = selected
+            .iter()
+            .map(|r: &Recipe| prices.recipe_cost(r).amount)
+            .enumerate()
+            .max_by_key(|(_, cost)| *cost);
+
+        let cheaper: Option<(usize, usize)> = most_expensive.and_then(|(index, max)| {
+            rest.iter()
+                .position(|r: &Recipe| prices.recipe_cost(r).amount < max)
+                .map(|position| (index, position))
+        });
+
+        match cheaper {
+            Some((index, position)) => {
+                let recipe: Recipe = rest.remove(position);
+                log::info!(
+                    "Swapped '{}' for '{}' to fit the budget",
+                    selected[index].name(),
+                    recipe.name()
+                );
+                selected[index] = recipe;
+            }
+            None => {
+                log::warn!(
+                    "Estimated cost {} exceeds the budget of {}, and there are no cheaper recipes",
+                    prices.format(cost),
+                    prices.format(budget)
+                );
+                return selected;
+            }
+        }
+    }
+}
+
+/// Log which ingredients are shared between the selected recipes. The overlap score itself is
+/// part of the output, see [crate::report::Report].
+fn report_overlap(recipes: &[Recipe]) {
+    for (item, count) in shared_items(recipes) {
+        log::info!("{} is used in {} recipes", item, count);
+    }
+}
+
+/// Remove all recipes that contain any excluded ingredient, and log how many recipes were excluded
+/// by each term. Which recipes were excluded, and why, is logged at info level as well.
+fn exclude(recipes: Vec<Recipe>, exclusions: &Exclusions) -> Vec<Recipe> {
+    if exclusions.is_empty() {
+        return recipes;
+    }
+
+    let total: usize = recipes.len();
+    let mut included: Vec<Recipe> = Vec::with_capacity(total);
+    let mut excluded: BTreeMap<String, usize> = BTreeMap::new();
+    for recipe in recipes {
+        match exclusions.check(&recipe) {
+            Some(exclusion) => {
+                log::info!(
+                    "Excluded '{}' since it contains {} (excluded by '{}')",
+                    recipe.name(),
+                    exclusion.item,
+                    exclusion.term
+                );
+                *excluded.entry(exclusion.term).or_default() += 1;
+            }
+            None => included.push(recipe),
+        }
+    }
+
+    if !excluded.is_empty() {
+        let reasons: Vec<String> = excluded
+            .iter()
+            .map(|(term, count)| format!("{} by '{}'", count, term))
+            .collect();
+        log::info!(
+            "Excluded {} of {} recipes ({})",
+            excluded.values().sum::<usize>(),
+            total,
+            reasons.join(", ")
+        );
+    }
+    included
+}
+
+fn exclude_complex(recipes: Vec<Recipe>, max: u32) -> Vec<Recipe> {
+    let total: usize = recipes.len();
+    let (included, excluded): (Vec<Recipe>, Vec<Recipe>) = recipes
+        .into_iter()
+        .partition(|r: &Recipe| complexity(r) <= max);
+
+    for recipe in &excluded {
+        log::info!(
+            "Excluded '{}' since its complexity {} exceeds {}",
+            recipe.name(),
+            complexity(recipe),
+            max
+        );
+    }
+    log::info!(
+        "Excluded {} of {} recipes due to complexity",
+        excluded.len(),
+        total
+    );
+    included
+}
+
+/// Order the recipes by seeded weighted random sampling without replacement, so that recipes with
+/// a higher weight are more likely to come first. If all recipes have the same weight, the order
+/// is kept as is, since the recipes are already expected to be shuffled.
+fn weighted_order(recipes: Vec<Recipe>, ratings: &Ratings, seed: u64) -> Vec<Recipe> {
+    let weights: Vec<f64> = recipes.iter().map(|r: &Recipe| ratings.weight(r)).collect();
+    if weights
+        .windows(2)
+        .all(|w| (w[0] - w[1]).abs() < f64::EPSILON)
+    {
+        return recipes;
+    }
+
+    // Each recipe is given the key u^(1/w) where u is uniformly random in [0, 1) and w is the
+    // weight of the recipe, and sorting by the key then yields a weighted sample (Efraimidis &
+    // Spirakis, 2006)
+    let mut rand = StdRng::seed_from_u64(seed);
+    let mut keyed: Vec<(f64, Recipe)> = recipes
+        .into_iter()
+        .zip(weights)
+        .map(|(recipe, weight)| {
+            let u: f64 = rand.gen();
+            let key: f64 = if weight > 0.0 {
+                u.powf(1.0 / weight)
+            } else {
+                0.0
+            };
+            (key, recipe)
+        })
+        .collect();
+
+    keyed.sort_by(|(k0, _), (k1, _)| k1.partial_cmp(k0).unwrap_or(Ordering::Equal));
+    keyed.into_iter().map(|(_, recipe)| recipe).collect()
+}
+
+/// Resolve each pin, which is either a path to a recipe file or the title of a recipe, into its
+/// recipe
+fn find_pinned(candidates: &[Recipe], pins: &[String]) -> Result<Vec<Recipe>, String> {
+    let mut pinned: Vec<Recipe> = Vec::with_capacity(pins.len());
+    for pin in pins {
+        match find_recipe(candidates, pin) {
+            Some(recipe) if pinned.contains(&recipe) => {
+                log::warn!("Recipe '{}' is pinned more than once", recipe.name())
+            }
+            Some(recipe) => pinned.push(recipe),
+            None => return Err(format!("No recipe found for pinned recipe '{}'", pin)),
+        }
+    }
+    Ok(pinned)
+}
+
+/// Find the recipe referred to by `key`, which is either a path to a recipe file or the title of
+/// a recipe. The recipe is primarily looked up among the `candidates`, but `key` may also be a
+/// path to a recipe file outside of the searched paths.
+pub fn find_recipe(candidates: &[Recipe], key: &str) -> Option<Recipe> {
+    let path = PathBuf::from(key);
+    match candidates
+        .iter()
+        .find(|r: &&Recipe| r.is_referred_to_by(key))
+    {
+        Some(recipe) => Some(recipe.clone()),
+        None if path.is_file() => Recipe::from_file(path),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::price::PriceList;
+    use crate::qty::Quantity;
+    use crate::rating::Ratings;
+    use crate::recipe::{Ingredient, Recipe};
+    use crate::select::{find_pinned, fit_budget, select_recipes, weighted_order, Criteria};
+    use std::path::PathBuf;
+
+    fn recipe(title: &str) -> Recipe {
+        Recipe::new(&format!("# {}", title), vec![], 4)
+    }
+
+    fn write_recipes(name: &str, titles: &[&str]) -> (PathBuf, Vec<PathBuf>) {
+        let dir: PathBuf =
+            std::env::temp_dir().join(format!("food-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files: Vec<PathBuf> = titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| {
+                let file: PathBuf = dir.join(format!("{}.md", i));
+                std::fs::write(&file, format!("# {}\n- eggs, 2", title)).unwrap();
+                file
+            })
+            .collect();
+        (dir, files)
+    }
+
+    #[test]
+    fn test_find_pinned() {
+        let candidates = vec![recipe("Pizza"), recipe("Pancakes"), recipe("Soup")];
+        let pins = vec!["soup".to_string(), "Pizza".to_string(), "SOUP".to_string()];
+        let pinned: Vec<Recipe> = find_pinned(&candidates, &pins).unwrap();
+        let titles: Vec<&str> = pinned.iter().map(|r| r.name()).collect();
+        assert_eq!(vec!["Soup", "Pizza"], titles);
+    }
+
+    #[test]
+    fn test_find_pinned_unknown() {
+        let candidates = vec![recipe("Pizza")];
+        let error: String = find_pinned(&candidates, &["Tacos".to_string()]).unwrap_err();
+        assert_eq!("No recipe found for pinned recipe 'Tacos'", error);
+    }
+
+    #[test]
+    fn test_weighted_order() {
+        let recipes = vec![recipe("Pizza"), recipe("Pancakes"), recipe("Soup")];
+        let ratings = Ratings::parse("- Pizza, 1\n- Soup, 5").unwrap();
+        let titles = |recipes: Vec<Recipe>| -> Vec<String> {
+            recipes.iter().map(|r| r.name().to_string()).collect()
+        };
+
+        let order = |seed: u64| titles(weighted_order(recipes.clone(), &ratings, seed));
+        assert_eq!(order(42), order(42));
+
+        let soup_first: usize = (0..200).filter(|seed| order(*seed)[0] == "Soup").count();
+        let pizza_first: usize = (0..200).filter(|seed| order(*seed)[0] == "Pizza").count();
+        assert!(soup_first > 80, "Soup came first {} times", soup_first);
+        assert!(
+            pizza_first < soup_first / 2,
+            "Pizza came first {} times",
+            pizza_first
+        );
+    }
+
+    #[test]
+    fn test_fit_budget() {
+        let recipe =
+            |title: &str, item: &str| Recipe::new(title, vec![Ingredient::parse(item).unwrap()], 4);
+        let prices = PriceList::parse("milk,1 l,10.00,SEK\ncream,5 dl,30.00,SEK").unwrap();
+        let pinned = vec![recipe("Porridge", "- milk, 5 dl")];
+        let selected = vec![
+            recipe("Cream sauce", "- cream, 5 dl"),
+            recipe("Pancakes", "- milk, 5 dl"),
+        ];
+        let rest = vec![
+            recipe("Whipped cream", "- cream, 3 dl"),
+            recipe("Soup", "- milk, 5 dl"),
+        ];
+
+        let within: Vec<Recipe> =
+            fit_budget(&pinned, selected.clone(), rest.clone(), &prices, 4000);
+        assert_eq!(selected, within);
+
+        let fitted: Vec<Recipe> = fit_budget(&pinned, selected, rest, &prices, 2500);
+        let titles: Vec<&str> = fitted.iter().map(|r| r.name()).collect();
+        assert_eq!(vec!["Soup", "Pancakes"], titles);
+    }
+
+    #[test]
+    fn test_fill_remaining_after_pinned() {
+        let (dir, files) = write_recipes("select", &["Pizza", "Pancakes", "Soup", "Tacos"]);
+        let criteria = Criteria {
+            limit: 3,
+            pins: vec!["Tacos".to_string()],
+            ..Criteria::default()
+        };
+        let selected: Vec<Recipe> = select_recipes(files.clone(), &criteria).unwrap();
+        let titles: Vec<&str> = selected.iter().map(|r| r.name()).collect();
+        assert_eq!(vec!["Tacos", "Pizza", "Pancakes"], titles);
+
+        let criteria = Criteria {
+            limit: 1,
+            pins: vec!["Soup".to_string(), "Tacos".to_string()],
+            ..Criteria::default()
+        };
+        let selected: Vec<Recipe> = select_recipes(files, &criteria).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let titles: Vec<&str> = selected.iter().map(|r| r.name()).collect();
+        assert_eq!(vec!["Soup", "Tacos"], titles);
+    }
+
+    #[test]
+    fn test_scale_pinned_outside_paths() {
+        let (dir, files) = write_recipes("scale", &["Pizza"]);
+        let soup: PathBuf = dir.join("soup.md");
+        std::fs::write(&soup, "# Soup\nservings: 2\n- eggs, 2").unwrap();
+        let criteria = Criteria {
+            limit: 1,
+            pins: vec![soup.to_string_lossy().to_string()],
+            serving_size: Some(4),
+            ..Criteria::default()
+        };
+        let selected: Vec<Recipe> = select_recipes(files, &criteria).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!("Soup", selected[0].name());
+        assert_eq!(Some(4), selected[0].servings);
+        assert_eq!(Quantity::Pieces(4), selected[0].ingredients[0].amount);
+    }
+}
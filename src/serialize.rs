@@ -0,0 +1,134 @@
+use crate::qty::{Quantity, Volume, Weight};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// The representation of quantities when they are serialized, which is the amount and the unit
+/// as written in recipes, such as `{"amount": 2, "unit": "dl"}`. The unit is left out for
+/// quantities that are counted in pieces.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Amount {
+    pub amount: u32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub unit: String,
+}
+
+impl From<Quantity> for Amount {
+    fn from(quantity: Quantity) -> Amount {
+        let (amount, unit): (u32, &str) = quantity.number_and_unit();
+        Amount {
+            amount,
+            unit: unit.to_string(),
+        }
+    }
+}
+
+impl From<Weight> for Amount {
+    fn from(weight: Weight) -> Amount {
+        Amount::from(Quantity::Weight(weight))
+    }
+}
+
+impl From<Volume> for Amount {
+    fn from(volume: Volume) -> Amount {
+        Amount::from(Quantity::Volume(volume))
+    }
+}
+
+impl TryFrom<Amount> for Quantity {
+    type Error = String;
+
+    fn try_from(amount: Amount) -> Result<Quantity, String> {
+        // Units are written out in full when they are formatted, except for spices (krm) which
+        // is not written out the same way as it is parsed
+        let unit: &str = match amount.unit.trim() {
+            "spices" => "krm",
+            unit => unit,
+        };
+        let quantity: Quantity = Quantity::parse(&format!("{} {}", amount.amount.max(1), unit))?;
+        match amount.amount {
+            0 => Ok(quantity.checked_sub(&quantity).unwrap()),
+            _ => Ok(quantity),
+        }
+    }
+}
+
+impl TryFrom<Amount> for Weight {
+    type Error = String;
+
+    fn try_from(amount: Amount) -> Result<Weight, String> {
+        match Quantity::try_from(amount)? {
+            Quantity::Weight(weight) => Ok(weight),
+            other => Err(format!(
+                "Expected a weight, but got '{}'",
+                other.to_string().trim()
+            )),
+        }
+    }
+}
+
+impl TryFrom<Amount> for Volume {
+    type Error = String;
+
+    fn try_from(amount: Amount) -> Result<Volume, String> {
+        match Quantity::try_from(amount)? {
+            Quantity::Volume(volume) => Ok(volume),
+            other => Err(format!(
+                "Expected a volume, but got '{}'",
+                other.to_string().trim()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::qty::{Quantity, Volume, Weight};
+    use crate::recipe::Recipe;
+
+    #[test]
+    fn test_quantities() {
+        let json: String = serde_json::to_string(&Quantity::Volume(Volume::Deciliter(2))).unwrap();
+        assert_eq!(r#"{"amount":2,"unit":"dl"}"#, json);
+        assert_eq!(
+            r#"{"amount":3}"#,
+            serde_json::to_string(&Quantity::Pieces(3)).unwrap()
+        );
+
+        for quantity in &[
+            Quantity::Pieces(3),
+            Quantity::Weight(Weight::Pounds(2)),
+            Quantity::Volume(Volume::Spices(1)),
+            Quantity::Volume(Volume::Ounces(4)),
+            Quantity::Custom(1, "bunch".to_string()),
+            Quantity::Volume(Volume::Milliliter(0)),
+        ] {
+            let json: String = serde_json::to_string(quantity).unwrap();
+            assert_eq!(quantity, &serde_json::from_str::<Quantity>(&json).unwrap());
+        }
+
+        let weight: Weight = serde_json::from_str(r#"{"amount":1,"unit":"kg"}"#).unwrap();
+        assert_eq!(Weight::Kilogram(1), weight);
+        assert!(serde_json::from_str::<Weight>(r#"{"amount":1,"unit":"dl"}"#).is_err());
+        assert!(serde_json::from_str::<Volume>(r#"{"amount":1,"unit":"kg"}"#).is_err());
+    }
+
+    #[test]
+    fn test_recipes() {
+        let content = "# Pizza\nservings: 4\n## Sauce\n- canned tomatoes, 400 g\n- basil";
+        let recipe: Recipe = Recipe::parse(content).unwrap();
+        let json: String = serde_json::to_string(&recipe).unwrap();
+        assert!(json.contains(r#""item":"canned tomatoes","amount":{"amount":400,"unit":"g"}"#));
+        assert_eq!(recipe, serde_json::from_str::<Recipe>(&json).unwrap());
+
+        let json = r##"{"title": "# Porridge", "ingredients": [
+                            {"item": "oats", "amount": {"amount": 2, "unit": "dl"}}
+                        ]}"##;
+        let recipe: Recipe = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            Quantity::Volume(Volume::Deciliter(2)),
+            recipe.ingredients[0].amount
+        );
+        assert_eq!(None, recipe.servings);
+        assert!(recipe.ingredients[0].sources.is_empty());
+    }
+}
@@ -0,0 +1,285 @@
+use crate::category::Categories;
+use crate::json::Json;
+use crate::nutrition::{Nutrients, NutritionTable, RecipeNutrition};
+use crate::package::{Choice, Packages};
+use crate::pantry::ShoppingList;
+use crate::price::{Cost, PriceList};
+use crate::qty::Units;
+use crate::recipe::{convert_unit, Ingredient, Recipe, Source};
+use crate::report::{capitalize, Report};
+use crate::target::{check, Miss, Target};
+use crate::template::Template;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use itertools::Itertools;
+use std::path::PathBuf;
+
+pub use crate::report::Format;
+
+/// How the shopping list is printed, and what is known about the ingredients on it
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub format: Format,
+    /// Template file to print the shopping list with, instead of [Options::format]
+    pub template: Option<PathBuf>,
+    /// The system of units that amounts are shown in
+    pub units: Units,
+    /// If the ingredients are grouped by category, in the text and markdown formats
+    pub group: bool,
+    pub show_sources: bool,
+    pub package_choice: Choice,
+    /// If the full text of each recipe is included in the HTML output
+    pub recipe_text: bool,
+    /// First day of the week of the iCalendar output
+    pub week: NaiveDate,
+    /// Time of dinner, for the events of the iCalendar output
+    pub dinner: Option<NaiveTime>,
+    /// When the iCalendar output is created
+    pub stamp: NaiveDateTime,
+    pub categories: Categories,
+    pub prices: PriceList,
+    pub packages: Packages,
+    pub nutrition: Option<NutritionTable>,
+    pub targets: Vec<Target>,
+}
+
+/// The shopping list of the `recipes`, with the ingredient `overlap` score if known, in the
+/// format or with the template of the `options`. The output ends with a newline, unless the
+/// template leaves it out.
+pub fn render(
+    recipes: &[Recipe],
+    list: &ShoppingList,
+    overlap: Option<u32>,
+    options: &Options,
+) -> Result<String, String> {
+    let report = Report {
+        overlap,
+        units: options.units,
+        ..Report::new(recipes, list)
+    };
+    let categories: &Categories = &options.categories;
+
+    if let Some(file) = &options.template {
+        let context: Json = report.to_context(categories);
+        return Template::from_file(file).and_then(|t: Template| t.render(&context));
+    }
+
+    let output: String = match options.format {
+        Format::Text => to_text(recipes, list, overlap, options),
+        Format::Json => report.to_json(categories).to_string(),
+        Format::Markdown => report.to_markdown(Some(categories).filter(|_| options.group)),
+        Format::Csv => report.to_csv(categories),
+        Format::Html => {
+            let texts: Vec<String> = if options.recipe_text {
+                recipes.iter().filter_map(Recipe::text).collect()
+            } else {
+                Vec::new()
+            };
+            report.to_html(categories, &texts)
+        }
+        Format::Ics => return Ok(report.to_ics(options.week, options.dinner, options.stamp)),
+    };
+    Ok(output + "\n")
+}
+
+/// The recipes and the shopping list as text, followed by the overlap of the ingredients, the
+/// estimated cost and the nutrition of the recipes, when known
+fn to_text(
+    recipes: &[Recipe],
+    list: &ShoppingList,
+    overlap: Option<u32>,
+    options: &Options,
+) -> String {
+    let mut lines: Vec<String> = recipes.iter().map(|r: &Recipe| r.to_string()).collect();
+
+    let (nutrition, misses): (Vec<(String, RecipeNutrition)>, Vec<Miss>) = match &options.nutrition
+    {
+        Some(table) => (
+            recipes
+                .iter()
+                .map(|r: &Recipe| (r.name().to_string(), table.recipe_nutrition(r)))
+                .collect(),
+            check(recipes, table, &options.targets),
+        ),
+        None => (Vec::new(), Vec::new()),
+    };
+
+    let prices: &PriceList = &options.prices;
+    let costs: Vec<(String, Cost)> = recipes
+        .iter()
+        .map(|r: &Recipe| (r.name().to_string(), prices.recipe_cost(r)))
+        .collect();
+    let total: Cost = prices.shopping_cost(&list.to_buy);
+
+    let mut packages: Packages = options.packages.clone();
+    packages.extend(prices.packages());
+    if options.group {
+        for (category, ingredients) in options.categories.group(list.to_buy.clone()) {
+            lines.push(format!("\n{}:", capitalize(&category)));
+            lines.extend(to_buy(&ingredients, &packages, options));
+        }
+    } else {
+        lines.extend(to_buy(&list.to_buy, &packages, options));
+    }
+
+    if !list.staples.is_empty() {
+        lines.push("\nStaples assumed in stock:".to_string());
+        for ingredient in &list.staples {
+            let line: String = convert_unit(ingredient, options.units).to_string();
+            lines.push(format!(
+                "{}{}",
+                line.trim_end(),
+                sources(ingredient, options)
+            ));
+        }
+    }
+
+    if let Some(score) = overlap {
+        lines.push(format!("\nIngredient overlap score: {}", score));
+    }
+
+    if !prices.is_empty() {
+        lines.push("\nEstimated cost:".to_string());
+        costs.iter().for_each(|(name, cost)| {
+            lines.push(format!("{}, {}", name, prices.format(cost.amount)))
+        });
+        lines.push(format!("Total, {}", prices.format(total.amount)));
+
+        let mut unpriced: Vec<&String> = costs
+            .iter()
+            .flat_map(|(_, cost)| &cost.unpriced)
+            .chain(&total.unpriced)
+            .collect();
+        unpriced.sort();
+        unpriced.dedup();
+        if !unpriced.is_empty() {
+            log::info!(
+                "Ingredients without a price: {}",
+                unpriced.iter().join(", ")
+            );
+        }
+    }
+
+    if !nutrition.is_empty() {
+        lines.extend(nutrition_lines(&nutrition));
+    }
+
+    if !misses.is_empty() {
+        lines.push("\nMissed nutrition targets:".to_string());
+        misses.iter().for_each(|m: &Miss| lines.push(m.to_string()));
+    }
+
+    lines.join("\n")
+}
+
+fn nutrition_lines(nutrition: &[(String, RecipeNutrition)]) -> Vec<String> {
+    let mut lines: Vec<String> = vec!["\nNutrition per serving:".to_string()];
+    for (name, n) in nutrition {
+        match n.per_serving {
+            Some(per_serving) => lines.push(format!("{}, {}", name, per_serving)),
+            None => lines.push(format!("{}, {} (whole recipe)", name, n.total)),
+        }
+    }
+
+    let total: Nutrients = nutrition
+        .iter()
+        .fold(Nutrients::default(), |acc, (_, n)| acc + n.total);
+    lines.push(format!("Total for all recipes, {}", total));
+
+    let mut missing: Vec<&String> = nutrition.iter().flat_map(|(_, n)| &n.missing).collect();
+    missing.sort();
+    missing.dedup();
+    if !missing.is_empty() {
+        log::info!(
+            "Ingredients without nutrition data: {}",
+            missing.iter().join(", ")
+        );
+    }
+    lines
+}
+
+/// Ingredients to buy, rounded up to whole packages when package sizes are known
+fn to_buy(ingredients: &[Ingredient], packages: &Packages, options: &Options) -> Vec<String> {
+    ingredients
+        .iter()
+        .map(
+            |ingredient: &Ingredient| match packages.round(ingredient, options.package_choice) {
+                Some(purchase) => format!("{}{}", purchase, sources(ingredient, options)),
+                None => {
+                    let line: String = convert_unit(ingredient, options.units).to_string();
+                    format!("{}{}", line.trim_end(), sources(ingredient, options))
+                }
+            },
+        )
+        .collect()
+}
+
+/// The recipes that the ingredient is needed for, such as ` (Pancakes 1 l, Pizza: Sauce 5 dl)`,
+/// if sources are to be shown
+fn sources(ingredient: &Ingredient, options: &Options) -> String {
+    if !options.show_sources || ingredient.sources.is_empty() {
+        return String::new();
+    }
+    let sources: Vec<String> = ingredient
+        .sources
+        .iter()
+        .map(|s: &Source| s.describe(options.units))
+        .collect();
+    format!(" ({})", sources.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pantry::ShoppingList;
+    use crate::recipe::{join_ingredients, Recipe};
+    use crate::shop::{render, Format, Options};
+    use chrono::NaiveDate;
+
+    fn recipes() -> Vec<Recipe> {
+        vec![Recipe::parse("# Pancakes\n- milk, 6 dl\n- eggs, 3").unwrap()]
+    }
+
+    fn options(format: Format) -> Options {
+        let week: NaiveDate = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        Options {
+            format,
+            template: None,
+            units: Default::default(),
+            group: false,
+            show_sources: true,
+            package_choice: Default::default(),
+            recipe_text: false,
+            week,
+            dinner: None,
+            stamp: week.and_hms_opt(12, 0, 0).unwrap(),
+            categories: Default::default(),
+            prices: Default::default(),
+            packages: Default::default(),
+            nutrition: None,
+            targets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_text() {
+        let recipes: Vec<Recipe> = recipes();
+        let list = ShoppingList {
+            to_buy: join_ingredients(recipes.clone()),
+            ..ShoppingList::default()
+        };
+        let output: String = render(&recipes, &list, Some(2), &options(Format::Text)).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines.contains(&"milk, 6 dl (Pancakes 6 dl)"));
+        assert_eq!(Some(&"Ingredient overlap score: 2"), lines.last());
+        assert!(output.ends_with('\n'));
+    }
+
+    #[test]
+    fn test_render_missing_template() {
+        let options = Options {
+            template: Some("missing.template".into()),
+            ..options(Format::Json)
+        };
+        let list = ShoppingList::default();
+        assert!(render(&recipes(), &list, None, &options).is_err());
+    }
+}
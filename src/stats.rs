@@ -0,0 +1,114 @@
+use crate::recipe::Recipe;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Number of ingredients listed as the most common ones
+const MOST_COMMON: usize = 10;
+
+/// Statistics about a collection of recipes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub recipes: usize,
+    /// Number of different ingredients in all recipes
+    pub ingredients: usize,
+    pub average_ingredients: f32,
+    /// Average cooking time in minutes of the recipes with a known time, and the number of such
+    /// recipes
+    pub average_time: Option<(u32, usize)>,
+    pub without_servings: usize,
+    /// The most common ingredients, with the number of recipes that use each of them
+    pub most_common: Vec<(String, usize)>,
+}
+
+impl Stats {
+    pub fn new(recipes: &[Recipe]) -> Stats {
+        let mut uses: HashMap<&str, usize> = HashMap::new();
+        for recipe in recipes {
+            for item in recipe.ingredients.iter().map(|i| i.item.as_str()).unique() {
+                *uses.entry(item).or_insert(0) += 1;
+            }
+        }
+
+        let times: Vec<u32> = recipes.iter().filter_map(|r: &Recipe| r.time).collect();
+        let average_time: Option<(u32, usize)> = match times.len() {
+            0 => None,
+            n => Some((times.iter().sum::<u32>() / n as u32, n)),
+        };
+
+        let total_ingredients: usize = recipes.iter().map(Recipe::size).sum();
+        let most_common: Vec<(String, usize)> = uses
+            .iter()
+            .sorted_by(|(a, n), (b, m)| m.cmp(n).then(a.cmp(b)))
+            .take(MOST_COMMON)
+            .map(|(item, n)| (item.to_string(), *n))
+            .collect();
+
+        Stats {
+            recipes: recipes.len(),
+            ingredients: uses.len(),
+            average_ingredients: total_ingredients as f32 / recipes.len().max(1) as f32,
+            average_time,
+            without_servings: recipes.iter().filter(|r| r.servings.is_none()).count(),
+            most_common,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Recipes: {}", self.recipes)?;
+        writeln!(
+            f,
+            "Ingredients: {} different, {:.1} per recipe on average",
+            self.ingredients, self.average_ingredients
+        )?;
+        if let Some((minutes, n)) = self.average_time {
+            writeln!(
+                f,
+                "Cooking time: {} min on average, of {} recipes",
+                minutes, n
+            )?;
+        }
+        if self.without_servings > 0 {
+            writeln!(f, "Recipes without servings: {}", self.without_servings)?;
+        }
+        if !self.most_common.is_empty() {
+            writeln!(f, "\nMost common ingredients:")?;
+        }
+        for (item, n) in &self.most_common {
+            let recipes: &str = if *n == 1 { "recipe" } else { "recipes" };
+            writeln!(f, "{}, {} {}", item, n, recipes)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::recipe::Recipe;
+    use crate::stats::Stats;
+
+    #[test]
+    fn test_stats() {
+        let recipes: Vec<Recipe> = vec![
+            "---\ntime: 30 min\n---\n# Pancakes\nservings: 4\n- milk, 6 dl\n- eggs, 3\n\
+             - milk, 1 dl",
+            "# Porridge\nservings: 2\n- oats, 2 dl\n- milk, 4 dl",
+            "# Omelette\n- eggs, 2",
+        ]
+        .into_iter()
+        .map(|content| Recipe::parse(content).unwrap())
+        .collect();
+
+        let stats = Stats::new(&recipes);
+        assert_eq!(3, stats.recipes);
+        assert_eq!(3, stats.ingredients);
+        assert_eq!(2.0, stats.average_ingredients);
+        assert_eq!(Some((30, 1)), stats.average_time);
+        assert_eq!(1, stats.without_servings);
+        assert_eq!(("eggs".to_string(), 2), stats.most_common[0]);
+        assert_eq!(("milk".to_string(), 2), stats.most_common[1]);
+        assert!(stats.to_string().starts_with("Recipes: 3\n"));
+    }
+}
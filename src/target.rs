@@ -0,0 +1,343 @@
+use crate::nutrition::{Nutrients, NutritionTable};
+use crate::recipe::Recipe;
+use std::fmt;
+use std::str::FromStr;
+
+/// How many candidates per recipe to select that will be considered when looking for recipes that
+/// meet the targets. A larger value makes it more likely that the targets are met, but gives less
+/// variation between selections.
+const CANDIDATES_PER_RECIPE: usize = 3;
+
+/// A target for the nutrition per serving of the selected recipes, such as `protein >= 25` (every
+/// recipe) or `avg kcal <= 700` (on average over all recipes)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Target {
+    pub nutrient: Nutrient,
+    pub bound: Bound,
+    pub value: f64,
+    /// If the target applies to the average of all recipes, rather than to every recipe
+    pub average: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nutrient {
+    Kcal,
+    Protein,
+    Fat,
+    Carbs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    AtLeast,
+    AtMost,
+}
+
+/// A target which is not met by the selected recipes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Miss {
+    pub target: Target,
+    /// The recipe which does not meet the target, or `None` for targets on the average
+    pub recipe: Option<String>,
+    pub actual: f64,
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let input: String = s.trim().to_lowercase();
+        let (average, rest): (bool, &str) = match input.strip_prefix("avg") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, input.as_str()),
+        };
+
+        let (nutrient, bound, value): (&str, Bound, &str) = match rest.split_once(">=") {
+            Some((nutrient, value)) => (nutrient, Bound::AtLeast, value),
+            None => match rest.split_once("<=") {
+                Some((nutrient, value)) => (nutrient, Bound::AtMost, value),
+                None => return Err(format!("Expected '>=' or '<=' in target: {}", s)),
+            },
+        };
+
+        let nutrient: Nutrient = match nutrient.trim() {
+            "kcal" => Nutrient::Kcal,
+            "protein" => Nutrient::Protein,
+            "fat" => Nutrient::Fat,
+            "carbs" => Nutrient::Carbs,
+            other => return Err(format!("Invalid nutrient in target: {}", other)),
+        };
+
+        let value: f64 = match value.trim().parse::<f64>() {
+            Ok(value) if value >= 0.0 => value,
+            _ => return Err(format!("Invalid value in target: {}", value.trim())),
+        };
+
+        Ok(Target {
+            nutrient,
+            bound,
+            value,
+            average,
+        })
+    }
+}
+
+impl Nutrient {
+    fn of(&self, nutrients: &Nutrients) -> f64 {
+        match self {
+            Nutrient::Kcal => nutrients.kcal,
+            Nutrient::Protein => nutrients.protein,
+            Nutrient::Fat => nutrients.fat,
+            Nutrient::Carbs => nutrients.carbs,
+        }
+    }
+
+    fn unit(&self) -> &str {
+        match self {
+            Nutrient::Kcal => "kcal",
+            Nutrient::Protein => "g protein",
+            Nutrient::Fat => "g fat",
+            Nutrient::Carbs => "g carbs",
+        }
+    }
+}
+
+impl Target {
+    /// How far from the target the value is, relative to the target, where 0 means that the
+    /// target is met
+    fn deviation(&self, actual: f64) -> f64 {
+        let diff: f64 = match self.bound {
+            Bound::AtLeast => self.value - actual,
+            Bound::AtMost => actual - self.value,
+        };
+        diff.max(0.0) / self.value.max(1.0)
+    }
+}
+
+/// Find all targets that are not met by the recipes. Recipes without a known number of servings
+/// are ignored, since their nutrition per serving is unknown.
+pub fn check(recipes: &[Recipe], table: &NutritionTable, targets: &[Target]) -> Vec<Miss> {
+    let per_serving: Vec<(&str, Nutrients)> = recipes
+        .iter()
+        .filter_map(|r: &Recipe| table.recipe_nutrition(r).per_serving.map(|n| (r.name(), n)))
+        .collect();
+
+    misses(&per_serving, targets)
+}
+
+fn misses(per_serving: &[(&str, Nutrients)], targets: &[Target]) -> Vec<Miss> {
+    let mut misses: Vec<Miss> = Vec::new();
+    for target in targets {
+        if target.average {
+            if per_serving.is_empty() {
+                continue;
+            }
+            let sum: f64 = per_serving.iter().map(|(_, n)| target.nutrient.of(n)).sum();
+            let actual: f64 = sum / per_serving.len() as f64;
+            if target.deviation(actual) > 0.0 {
+                misses.push(Miss {
+                    target: *target,
+                    recipe: None,
+                    actual,
+                });
+            }
+        } else {
+            for (name, nutrients) in per_serving {
+                let actual: f64 = target.nutrient.of(nutrients);
+                if target.deviation(actual) > 0.0 {
+                    misses.push(Miss {
+                        target: *target,
+                        recipe: Some(name.to_string()),
+                        actual,
+                    });
+                }
+            }
+        }
+    }
+    misses
+}
+
+/// Score how far the recipes are from meeting the targets, where 0 means all targets are met
+fn score(per_serving: &[(&str, Nutrients)], targets: &[Target]) -> f64 {
+    misses(per_serving, targets)
+        .iter()
+        .map(|miss: &Miss| miss.target.deviation(miss.actual))
+        .sum()
+}
+
+/// Improve how well the `selected` recipes, together with the `pinned` recipes, meet the targets,
+/// by swapping selected recipes for recipes among the first of the `rest` of the candidates. The
+/// swap that improves the most is made until no swap improves any further, and the order of the
+/// candidates decides between equally good swaps. Pinned recipes are never swapped, and recipes
+/// with unknown nutrition are never swapped in. Returns the selected recipes and the rest of the
+/// candidates.
+pub fn select_for_targets(
+    pinned: &[Recipe],
+    mut selected: Vec<Recipe>,
+    mut rest: Vec<Recipe>,
+    table: &NutritionTable,
+    targets: &[Target],
+) -> (Vec<Recipe>, Vec<Recipe>) {
+    let window: usize = selected.len().max(1) * CANDIDATES_PER_RECIPE;
+    let mut unused: Vec<Recipe> = rest.split_off(window.min(rest.len()));
+
+    let nutrition = |r: &Recipe| table.recipe_nutrition(r).per_serving;
+    let fixed: Vec<(&str, Nutrients)> = pinned
+        .iter()
+        .filter_map(|r: &Recipe| nutrition(r).map(|n| (r.name(), n)))
+        .collect();
+    let mut chosen: Vec<Option<Nutrients>> = selected.iter().map(nutrition).collect();
+    let mut others: Vec<Option<Nutrients>> = rest.iter().map(nutrition).collect();
+
+    let evaluate = |selected: &[Recipe], chosen: &[Option<Nutrients>]| -> f64 {
+        let mut per_serving: Vec<(&str, Nutrients)> = fixed.clone();
+        selected
+            .iter()
+            .zip(chosen)
+            .filter_map(|(r, n)| n.map(|n| (r.name(), n)))
+            .for_each(|entry| per_serving.push(entry));
+        score(&per_serving, targets)
+    };
+
+    let mut current: f64 = evaluate(&selected, &chosen);
+    while current > 0.0 {
+        let mut best: Option<(f64, usize, usize)> = None;
+        for i in 0..selected.len() {
+            for j in (0..rest.len()).filter(|j| others[*j].is_some()) {
+                let mut swapped: Vec<Option<Nutrients>> = chosen.clone();
+                swapped[i] = others[j];
+                let score: f64 = evaluate(&selected, &swapped);
+                if score < best.map(|(s, _, _)| s).unwrap_or(current) {
+                    best = Some((score, i, j));
+                }
+            }
+        }
+
+        let (score, i, j) = match best {
+            Some(best) => best,
+            None => break,
+        };
+        log::info!(
+            "Swapped '{}' for '{}' to meet nutrition targets",
+            selected[i].name(),
+            rest[j].name()
+        );
+        std::mem::swap(&mut selected[i], &mut rest[j]);
+        std::mem::swap(&mut chosen[i], &mut others[j]);
+        current = score;
+    }
+
+    rest.append(&mut unused);
+    (selected, rest)
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bound: &str = match self.bound {
+            Bound::AtLeast => "at least",
+            Bound::AtMost => "at most",
+        };
+        let scope: &str = if self.average {
+            "on average"
+        } else {
+            "per serving"
+        };
+        write!(
+            f,
+            "{} {} {} {}",
+            bound,
+            self.value,
+            self.nutrient.unit(),
+            scope
+        )
+    }
+}
+
+impl fmt::Display for Miss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit: &str = self.target.nutrient.unit();
+        match &self.recipe {
+            Some(recipe) => write!(
+                f,
+                "{} has {:.0} {} per serving, target is {}",
+                recipe, self.actual, unit, self.target
+            ),
+            None => write!(
+                f,
+                "Average is {:.0} {} per serving, target is {}",
+                self.actual, unit, self.target
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nutrition::NutritionTable;
+    use crate::recipe::{Ingredient, Recipe};
+    use crate::target::{check, select_for_targets, Bound, Nutrient, Target};
+
+    const TABLE: &str = "name,kcal,protein,fat,carbs\n\
+                         chicken,120,25,2,0\n\
+                         pasta,350,12,2,70\n\
+                         cream,340,2,35,3\n";
+
+    fn recipe(title: &str, items: &[&str]) -> Recipe {
+        let ingredients: Vec<Ingredient> = items
+            .iter()
+            .map(|i| Ingredient::parse(i).unwrap())
+            .collect();
+        Recipe::new(title, ingredients, 1)
+    }
+
+    #[test]
+    fn test_parse_target() {
+        let target: Target = "protein >= 25".parse().unwrap();
+        assert_eq!(Nutrient::Protein, target.nutrient);
+        assert_eq!(Bound::AtLeast, target.bound);
+        assert!(!target.average);
+        let target: Target = "avg kcal<=700".parse().unwrap();
+        assert_eq!(Bound::AtMost, target.bound);
+        assert!(target.average);
+        assert!("fibre >= 10".parse::<Target>().is_err());
+        assert!("protein = 10".parse::<Target>().is_err());
+    }
+
+    #[test]
+    fn test_check_targets() {
+        let table = NutritionTable::parse(TABLE).unwrap();
+        let recipes = vec![
+            recipe("Chicken", &["- chicken, 200 g"]),
+            recipe("Creamy pasta", &["- pasta, 100 g", "- cream, 100 g"]),
+        ];
+        let targets: Vec<Target> = vec![
+            "protein >= 25".parse().unwrap(),
+            "avg kcal <= 450".parse().unwrap(),
+        ];
+        let misses = check(&recipes, &table, &targets);
+        assert_eq!(2, misses.len());
+        assert_eq!(Some("Creamy pasta".to_string()), misses[0].recipe);
+        assert_eq!(None, misses[1].recipe);
+        assert_eq!(
+            "Average is 465 kcal per serving, target is at most 400 kcal on average",
+            check(&recipes, &table, &["avg kcal <= 400".parse().unwrap()])[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_select_for_targets() {
+        let table = NutritionTable::parse(TABLE).unwrap();
+        let selected = vec![recipe(
+            "Creamy pasta",
+            &["- pasta, 100 g", "- cream, 100 g"],
+        )];
+        let rest = vec![
+            recipe("Pasta", &["- pasta, 100 g"]),
+            recipe("Chicken", &["- chicken, 200 g"]),
+        ];
+        let targets: Vec<Target> = vec!["protein >= 25".parse().unwrap()];
+        let (selected, rest) = select_for_targets(&[], selected, rest, &table, &targets);
+        assert_eq!("Chicken", selected[0].name());
+        assert_eq!(2, rest.len());
+    }
+}
@@ -0,0 +1,329 @@
+use crate::json::Json;
+use std::borrow::Cow;
+use std::path::Path;
+
+/// A template in a small subset of the Handlebars syntax, which is rendered with a JSON value as
+/// context. The supported tags are
+/// - `{{name}}` for the value of a field, where `name` may be a path such as `recipe.title`,
+///   or `this` for the current value
+/// - `{{#each list}}...{{/each}}` to repeat a part for every value in a list, with `@index`
+///   and `@number` for the index of the value, starting at 0 and 1 respectively
+/// - `{{#if name}}...{{else}}...{{/if}}` for a part that is only rendered if a field has a
+///   value that is not null, false, zero, empty or an empty list, with an optional else part
+/// - `{{#unless name}}...{{/unless}}` for the opposite of `if`
+/// - `{{! comment }}` for comments
+///
+/// Fields are first looked up in the current value, then in the values of enclosing `each`
+/// blocks. Lines with only a block tag or a comment are left out of the output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Value(String),
+    Each(String, Vec<Node>),
+    If(String, Vec<Node>, Vec<Node>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Text(String),
+    Tag(String),
+}
+
+impl Template {
+    pub fn parse(content: &str) -> Result<Template, String> {
+        let tokens: Vec<Token> = trim_standalone(tokenize(content)?);
+        let mut tokens = tokens.into_iter();
+        let (nodes, end): (Vec<Node>, Option<String>) = parse_nodes(&mut tokens)?;
+        match end {
+            Some(tag) => Err(format!("Unexpected {{{{{}}}}} in template", tag)),
+            None => Ok(Template { nodes }),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Template, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Template::parse(&content),
+            Err(e) => Err(format!("Unable to read template from {:?}: {}", path, e)),
+        }
+    }
+
+    pub fn render(&self, context: &Json) -> Result<String, String> {
+        let mut output = String::new();
+        render_nodes(&self.nodes, &mut vec![Scope::new(context)], &mut output)?;
+        Ok(output)
+    }
+}
+
+fn tokenize(content: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut rest: &str = content;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let end: usize = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => return Err("Unclosed tag in template, expected '}}'".to_string()),
+        };
+        tokens.push(Token::Tag(rest[start + 2..end].trim().to_string()));
+        rest = &rest[end + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+    Ok(tokens)
+}
+
+fn is_block_tag(tag: &str) -> bool {
+    tag.starts_with('#') || tag.starts_with('/') || tag.starts_with('!') || tag == "else"
+}
+
+/// Remove the whitespace and the line break around block tags and comments that are on a line
+/// of their own
+fn trim_standalone(mut tokens: Vec<Token>) -> Vec<Token> {
+    // The part of each text to keep, since a text between two block tags may be cut at both ends
+    let mut keep: Vec<(usize, usize)> = tokens
+        .iter()
+        .map(|t: &Token| match t {
+            Token::Text(text) => (0, text.len()),
+            Token::Tag(_) => (0, 0),
+        })
+        .collect();
+
+    for i in 0..tokens.len() {
+        match &tokens[i] {
+            Token::Tag(tag) if is_block_tag(tag) => {}
+            _ => continue,
+        }
+
+        let before: Option<usize> = match i.checked_sub(1).map(|j| &tokens[j]) {
+            None => Some(0),
+            Some(Token::Text(text)) => {
+                let line_start: usize = text.rfind('\n').map(|n| n + 1).unwrap_or(0);
+                let at_start: bool = line_start > 0 || i == 1;
+                Some(line_start).filter(|_| at_start && text[line_start..].trim().is_empty())
+            }
+            Some(Token::Tag(_)) => None,
+        };
+        let after: Option<usize> = match tokens.get(i + 1) {
+            None => Some(0),
+            Some(Token::Text(text)) => match text.find('\n') {
+                Some(n) if text[..n].trim().is_empty() => Some(n + 1),
+                None if i + 2 == tokens.len() && text.trim().is_empty() => Some(text.len()),
+                _ => None,
+            },
+            Some(Token::Tag(_)) => None,
+        };
+
+        if let (Some(line_start), Some(line_end)) = (before, after) {
+            if i > 0 {
+                keep[i - 1].1 = line_start;
+            }
+            if i + 1 < tokens.len() {
+                keep[i + 1].0 = line_end;
+            }
+        }
+    }
+
+    for (token, (start, end)) in tokens.iter_mut().zip(keep) {
+        if let Token::Text(text) = token {
+            *text = text.get(start..end.max(start)).unwrap_or("").to_string();
+        }
+    }
+    tokens.retain(|t: &Token| t != &Token::Text(String::new()));
+    tokens
+}
+
+/// Parse nodes until the end of the tokens, or until a closing tag or `else`, which is returned
+fn parse_nodes(
+    tokens: &mut impl Iterator<Item = Token>,
+) -> Result<(Vec<Node>, Option<String>), String> {
+    let mut nodes: Vec<Node> = Vec::new();
+    while let Some(token) = tokens.next() {
+        let tag: String = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text));
+                continue;
+            }
+            Token::Tag(tag) => tag,
+        };
+
+        if tag.starts_with('/') || tag == "else" {
+            return Ok((nodes, Some(tag)));
+        } else if tag.starts_with('!') {
+            continue;
+        } else if let Some(block) = tag.strip_prefix('#') {
+            let (name, path): (&str, String) = match block.split_once(char::is_whitespace) {
+                Some((name, path)) => (name, path.trim().to_string()),
+                None => return Err(format!("Missing field in {{{{{}}}}} in template", tag)),
+            };
+            let (body, end) = parse_nodes(tokens)?;
+            let (otherwise, end) = match end.as_deref() {
+                Some("else") if name != "each" => parse_nodes(tokens)?,
+                _ => (Vec::new(), end),
+            };
+            if end.as_deref() != Some(&format!("/{}", name)) {
+                return Err(format!("Missing {{{{/{}}}}} in template", name));
+            }
+            let node: Node = match name {
+                "each" => Node::Each(path, body),
+                "if" => Node::If(path, body, otherwise),
+                "unless" => Node::If(path, otherwise, body),
+                _ => return Err(format!("Unknown block {{{{#{}}}}} in template", name)),
+            };
+            nodes.push(node);
+        } else if tag.is_empty() {
+            return Err("Empty tag in template".to_string());
+        } else {
+            nodes.push(Node::Value(tag));
+        }
+    }
+    Ok((nodes, None))
+}
+
+/// A value that fields are looked up in, with the index of the value if it is part of a list
+struct Scope<'a> {
+    value: &'a Json,
+    index: Option<usize>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(value: &'a Json) -> Scope<'a> {
+        Scope { value, index: None }
+    }
+}
+
+fn render_nodes<'a>(
+    nodes: &[Node],
+    scopes: &mut Vec<Scope<'a>>,
+    output: &mut String,
+) -> Result<(), String> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Value(path) => match lookup(scopes, path).as_deref() {
+                Some(Json::String(s)) => output.push_str(s),
+                Some(Json::Number(n)) => output.push_str(&Json::Number(*n).to_string()),
+                Some(Json::Null) | None => {}
+                Some(_) => return Err(format!("Cannot print list or object '{}'", path)),
+            },
+            Node::Each(path, body) => {
+                let values: &[Json] = match lookup(scopes, path) {
+                    Some(Cow::Borrowed(Json::Array(values))) => values,
+                    Some(Cow::Borrowed(Json::Null)) | None => &[],
+                    Some(_) => return Err(format!("Cannot repeat '{}', it is not a list", path)),
+                };
+                for (index, value) in values.iter().enumerate() {
+                    scopes.push(Scope {
+                        value,
+                        index: Some(index),
+                    });
+                    let result = render_nodes(body, scopes, output);
+                    scopes.pop();
+                    result?;
+                }
+            }
+            Node::If(path, body, otherwise) => {
+                if is_truthy(lookup(scopes, path).as_deref()) {
+                    render_nodes(body, scopes, output)?;
+                } else {
+                    render_nodes(otherwise, scopes, output)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the value of the field with the path, first in the innermost scope and then in the
+/// enclosing ones
+fn lookup<'a>(scopes: &[Scope<'a>], path: &str) -> Option<Cow<'a, Json>> {
+    let index: Option<usize> = scopes.last().and_then(|s: &Scope| s.index);
+    match path {
+        "this" | "." => return scopes.last().map(|s: &Scope<'a>| Cow::Borrowed(s.value)),
+        "@index" => return index.map(|i| Cow::Owned(Json::Number(i as f64))),
+        "@number" => return index.map(|i| Cow::Owned(Json::Number((i + 1) as f64))),
+        _ => {}
+    }
+
+    let path: &str = path.strip_prefix("this.").unwrap_or(path);
+    let fields: Vec<&str> = path.split('.').collect();
+    scopes
+        .iter()
+        .rev()
+        .find_map(|scope: &Scope<'a>| field(scope.value, fields[0]))
+        .and_then(|value: &'a Json| fields[1..].iter().try_fold(value, |v, name| field(v, name)))
+        .map(Cow::Borrowed)
+}
+
+fn field<'a>(value: &'a Json, name: &str) -> Option<&'a Json> {
+    match value {
+        Json::Object(members) => members.iter().find(|(key, _)| key == name).map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+fn is_truthy(value: Option<&Json>) -> bool {
+    match value {
+        None | Some(Json::Null) => false,
+        Some(Json::Number(n)) => *n != 0.0,
+        Some(Json::String(s)) => !s.is_empty(),
+        Some(Json::Array(values)) => !values.is_empty(),
+        Some(Json::Object(_)) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::Json;
+    use crate::template::Template;
+
+    fn context() -> Json {
+        let item = |name: &str, quantity: &str| {
+            Json::object(vec![
+                ("item", Json::from(name)),
+                ("quantity", Json::from(quantity)),
+            ])
+        };
+        Json::object(vec![
+            ("title", Json::from("Weekly plan")),
+            ("servings", Json::Null),
+            (
+                "items",
+                Json::Array(vec![item("milk", "1 l"), item("eggs", "")]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_render_values_and_blocks() {
+        let template = "# {{ title }}\n\
+                        {{#each items}}\n\
+                        {{@number}}. {{item}}{{#if quantity}}, {{quantity}}{{/if}} ({{title}})\n\
+                        {{/each}}\n\
+                        {{#unless servings}}No servings{{else}}{{servings}}{{/unless}}";
+        let output: String = Template::parse(template)
+            .unwrap()
+            .render(&context())
+            .unwrap();
+        assert_eq!(
+            "# Weekly plan\n1. milk, 1 l (Weekly plan)\n2. eggs (Weekly plan)\nNo servings",
+            output
+        );
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(Template::parse("{{#each items}}{{item}}").is_err());
+        assert!(Template::parse("{{#if items}}{{/each}}").is_err());
+        assert!(Template::parse("{{#with items}}{{/with}}").is_err());
+        assert!(Template::parse("{{title").is_err());
+        let template = Template::parse("{{items}}").unwrap();
+        assert!(template.render(&context()).is_err());
+    }
+}